@@ -0,0 +1,78 @@
+use crate::command::extract_doc_comments;
+use crate::command::params::{check_option_count, signatures, CommandArg, CommandParam};
+use crate::utils::MacroError;
+use crate::utils::MacroError::*;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields};
+
+/// Implements `CommandArguments` for a struct with named fields, each field becoming an option
+/// (or a group of options with `#[flatten]`) in declaration order
+pub fn derive(input: DeriveInput) -> Result<TokenStream, MacroError> {
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(SynError(syn::Error::new(
+                    input.span(),
+                    "CommandArguments can only be derived for structs with named fields",
+                )))
+            }
+        },
+        _ => {
+            return Err(SynError(syn::Error::new(
+                input.span(),
+                "CommandArguments can only be derived for structs",
+            )))
+        }
+    };
+
+    let mut params = Vec::new();
+    let mut idents = Vec::new();
+    for field in fields {
+        let ident = field.ident.clone().expect("Named fields have identifiers");
+        if field.attrs.iter().any(|a| a.path.is_ident("flatten")) {
+            params.push(CommandParam::Flatten(Box::new(field.ty.clone())));
+        } else {
+            let mut arg =
+                CommandArg::new(ident.to_string(), field.span(), &field.ty, &field.attrs)?;
+            arg.describe(extract_doc_comments(&field.attrs).map(|d| d.trim().to_string()));
+            params.push(CommandParam::Argument(Box::new(arg)));
+        }
+        idents.push(ident);
+    }
+
+    let ctx = quote!(ctx);
+    let values = params.iter().map(|param| match param {
+        CommandParam::Flatten(ty) => quote! {
+            <#ty as ::ataraxy::framework::command::argument::CommandArguments>::from_arguments(ctx, args).await?
+        },
+        CommandParam::Argument(arg) => arg.as_value(&ctx),
+        _ => unreachable!(),
+    });
+    let name = &input.ident;
+    check_option_count(&params, &format!("`{}`", name), name.span())?;
+    let signatures = signatures(&params)?;
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[::ataraxy::async_trait]
+        impl #impl_generics ::ataraxy::framework::command::argument::CommandArguments for #name #ty_generics #where_clause {
+            fn signatures() -> ::std::vec::Vec<::ataraxy::framework::command::argument::CommandArgumentSignature> {
+                #signatures
+            }
+
+            async fn from_arguments(
+                ctx: &::ataraxy::Context,
+                args: &mut ::ataraxy::framework::command::argument::ArgumentList,
+            ) -> ::core::result::Result<Self, ::ataraxy::framework::command::argument::ArgumentError> {
+                ::core::result::Result::Ok(Self {
+                    #(#idents: #values),*
+                })
+            }
+        }
+    }
+    .into())
+}
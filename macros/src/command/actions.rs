@@ -1,23 +1,40 @@
-
+use crate::command::params::CommandParam;
 use proc_macro2::TokenStream;
-use quote::{quote};
+use quote::{format_ident, quote};
 
-pub fn create_slash_command_action(ctx: bool, num_args: u8) -> TokenStream {
-    let args: Vec<TokenStream> = (0..num_args)
-        .into_iter()
-        .map(|_| {
-            quote! {
-                args.arg(&ctx).await
-            }
+pub fn create_slash_command_action(params: &[CommandParam]) -> TokenStream {
+    let ctx = quote!(&ctx);
+    let mut bindings = Vec::new();
+    let args: Vec<TokenStream> = params
+        .iter()
+        .enumerate()
+        .map(|(i, param)| {
+            let value = match param {
+                CommandParam::Context { reference: false } => return quote!(ctx.clone()),
+                CommandParam::Context { reference: true } => return quote!(&ctx),
+                CommandParam::Extract(ty) => quote! {
+                    <#ty as ::ataraxy::framework::command::extract::FromContext>::from_context(&ctx).await?
+                },
+                CommandParam::Flatten(ty) => quote! {
+                    <#ty as ::ataraxy::framework::command::argument::CommandArguments>::from_arguments(&ctx, &mut args).await?
+                },
+                CommandParam::Argument(arg) => arg.as_value(&ctx),
+            };
+            let ident = format_ident!("arg{}", i);
+            bindings.push(quote! {
+                let #ident = #value;
+            });
+            quote!(#ident)
         })
         .collect();
-    let context = if ctx { quote!(ctx.clone(),) } else { quote!() };
     quote! {
         |ctx, args| {
             let mut args = args.clone();
 
             Box::pin(async move {
-                inner(#context #(#args),*).await
+                #(#bindings)*
+                inner(#(#args),*).await;
+                ::core::result::Result::Ok::<(), ::ataraxy::framework::command::argument::ArgumentError>(())
             })
         }
     }
@@ -1,13 +1,14 @@
 mod actions;
-mod params;
+pub mod params;
 
 use crate::utils::MacroError::*;
-use crate::utils::{quote_option, quote_vec, MacroError, Multiple};
+use crate::utils::{quote_localizations, quote_option, quote_vec, MacroError, Multiple};
 use proc_macro::TokenStream;
 use quote::quote;
+use std::collections::HashMap;
 use syn::spanned::Spanned;
 use syn::Lit::Str;
-use syn::{FnArg, ItemFn, Meta};
+use syn::{Attribute, FnArg, ItemFn, Meta};
 
 #[derive(Default, Debug, darling::FromMeta)]
 #[darling(default)]
@@ -15,11 +16,13 @@ pub struct CommandArgs {
     name: Option<String>,
     guild: Option<Multiple<u64>>,
     description: Option<String>,
+    name_localizations: Option<HashMap<String, String>>,
+    description_localizations: Option<HashMap<String, String>>,
 }
 
-fn extract_doc_comments(function: &ItemFn) -> Option<String> {
+pub fn extract_doc_comments(attrs: &[Attribute]) -> Option<String> {
     let mut doc_lines = String::new();
-    for attr in &function.attrs {
+    for attr in attrs {
         if attr.path == quote::format_ident!("doc").into() {
             if let Ok(Meta::NameValue(nv)) = attr.parse_meta() {
                 if let Str(literal) = nv.lit {
@@ -42,7 +45,7 @@ fn extract_doc_comments(function: &ItemFn) -> Option<String> {
 pub fn command(args: CommandArgs, function: ItemFn) -> Result<TokenStream, MacroError> {
     let description = match args.description {
         Some(desc) => desc,
-        None => extract_doc_comments(&function).ok_or_else(|| SynError(syn::Error::new(function.sig.span(), "You must provide a slash command description in either doc comments of the function or as the `description` parameter to the macro.")))?,
+        None => extract_doc_comments(&function.attrs).ok_or_else(|| SynError(syn::Error::new(function.sig.span(), "You must provide a slash command description in either doc comments of the function or as the `description` parameter to the macro.")))?,
     };
 
     let name = args
@@ -69,13 +72,19 @@ pub fn command(args: CommandArgs, function: ItemFn) -> Result<TokenStream, Macro
     }
 
     let parameters = params::get_args(&function)?;
+    params::check_option_count(
+        &parameters.params,
+        &format!("Command `{}`", name),
+        function.sig.ident.span(),
+    )?;
 
-    let signature = parameters.as_signature();
+    let signature = parameters.as_signature()?;
 
-    let action =
-        actions::create_slash_command_action(parameters.context, parameters.args.len() as u8);
+    let action = actions::create_slash_command_action(&parameters.params);
 
     let guilds = quote_option(&args.guild.map(|g| quote_vec(&g.0)));
+    let name_localizations = quote_localizations(&args.name_localizations, "");
+    let description_localizations = quote_localizations(&args.description_localizations, "");
 
     Ok(quote! {
         #visibility fn #func_name() -> ::ataraxy::Command {
@@ -84,6 +93,8 @@ pub fn command(args: CommandArgs, function: ItemFn) -> Result<TokenStream, Macro
             ::ataraxy::Command {
                 name: #name.to_string(),
                 description: #description.to_string(),
+                name_localizations: #name_localizations,
+                description_localizations: #description_localizations,
                 arguments: #signature,
                 guilds: #guilds,
                 action: ::ataraxy::framework::command::CommandHandler(#action),
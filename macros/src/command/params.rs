@@ -1,11 +1,20 @@
 use crate::utils::MacroError::*;
-use crate::utils::{quote_option, quote_vec, MacroError, Multiple};
+use crate::utils::{
+    option_inner, quote_localizations, quote_option, quote_vec, MacroError, Multiple,
+};
+use darling::util::SpannedValue;
+use std::collections::HashMap;
 
 use proc_macro2::{Span, TokenStream};
-use quote::{quote, quote_spanned};
+use quote::{quote, quote_spanned, ToTokens};
 use syn::spanned::Spanned;
 use syn::FnArg::Typed;
-use syn::{FnArg, ItemFn, Meta, Pat, Type};
+use syn::{Attribute, Expr, FnArg, GenericArgument, ItemFn, Lit, Meta, Pat, PathArguments, Type};
+
+/// Maximum length of an option description allowed by Discord
+const MAX_DESCRIPTION_LEN: usize = 100;
+/// Maximum number of options of a command allowed by Discord
+const MAX_OPTIONS: usize = 25;
 
 #[derive(Debug, Clone, darling::FromMeta)]
 enum ChannelType {
@@ -29,16 +38,163 @@ enum ChannelType {
     All,
 }
 
+/// A size in bytes, either as an integer or a string with a unit: `max_size = "8MB"`
+#[derive(Debug, Clone, Copy)]
+struct ByteSize(u64);
+
+impl darling::FromMeta for ByteSize {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        let value = value.trim();
+        let split = value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len());
+        let (amount, unit) = value.split_at(split);
+        let amount: u64 = amount
+            .parse()
+            .map_err(|_| darling::Error::custom(format!("Invalid size `{}`", value)))?;
+        let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "KB" | "KIB" => 1024,
+            "MB" | "MIB" => 1024 * 1024,
+            "GB" | "GIB" => 1024 * 1024 * 1024,
+            _ => {
+                return Err(darling::Error::custom(format!(
+                    "Unknown size unit `{}`, expected B, KB, MB or GB",
+                    unit
+                )))
+            }
+        };
+        Ok(Self(amount * multiplier))
+    }
+
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        match value {
+            Lit::Int(i) => Ok(Self(i.base10_parse().map_err(darling::Error::custom)?)),
+            Lit::Str(s) => Self::from_string(&s.value()),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+    }
+}
+
+/// A bound of an option, either a number (`min = 1`) or a string parsed by the argument type (`min = "1m"`)
+#[derive(Debug, Clone)]
+enum Bound {
+    Number(f64),
+    Text(String),
+}
+
+impl Bound {
+    fn number(bound: &Option<SpannedValue<Self>>) -> Option<f64> {
+        match bound.as_deref() {
+            Some(Bound::Number(n)) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn text(bound: &Option<SpannedValue<Self>>) -> Option<String> {
+        match bound.as_deref() {
+            Some(Bound::Text(t)) => Some(t.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Types whose bounds are numbers, bounds given as strings would be ignored
+const NUMBER_TYPES: &[&str] = &[
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "usize",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "isize",
+    "f32",
+    "f64",
+    "NonZeroU8",
+    "NonZeroU16",
+    "NonZeroU32",
+    "NonZeroU64",
+    "NonZeroUsize",
+    "NonZeroI8",
+    "NonZeroI16",
+    "NonZeroI32",
+    "NonZeroI64",
+    "NonZeroIsize",
+];
+
+/// Types whose bounds are strings parsed by the type, bounds given as numbers would be ignored
+const TEXT_BOUND_TYPES: &[&str] = &["Duration", "DateTime"];
+
+/// Checks that `min` and `max` have the kind of bound the argument type reads
+fn check_bounds(ty: &Type, options: &CommandArgOptions) -> Result<(), MacroError> {
+    let inner = option_inner(ty).or_else(|| vec_inner(ty)).unwrap_or(ty);
+    let name = match inner {
+        Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    };
+    let name = match name {
+        Some(name) => name,
+        None => return Ok(()),
+    };
+    for bound in options.min.iter().chain(options.max.iter()) {
+        match &**bound {
+            Bound::Text(_) if NUMBER_TYPES.contains(&name.as_str()) => {
+                return Err(SynError(syn::Error::new(
+                    bound.span(),
+                    "Bounds of number options must be numbers, ie `min = 5`",
+                )))
+            }
+            Bound::Number(_) if TEXT_BOUND_TYPES.contains(&name.as_str()) => {
+                return Err(SynError(syn::Error::new(
+                    bound.span(),
+                    "Bounds of duration and date options must be strings, ie `min = \"1m\"`",
+                )))
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+impl darling::FromMeta for Bound {
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        match value {
+            Lit::Int(i) => Ok(Bound::Number(
+                i.base10_parse().map_err(darling::Error::custom)?,
+            )),
+            Lit::Float(f) => Ok(Bound::Number(
+                f.base10_parse().map_err(darling::Error::custom)?,
+            )),
+            Lit::Str(s) => Ok(Bound::Text(s.value())),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+    }
+}
+
 #[derive(Default, Debug, darling::FromMeta)]
 #[darling(default)]
 pub struct CommandArgOptions {
-    min: Option<f64>,
-    max: Option<f64>,
+    min: Option<SpannedValue<Bound>>,
+    max: Option<SpannedValue<Bound>>,
     min_len: Option<u64>,
     max_len: Option<u64>,
     channel_type: Option<Multiple<ChannelType>>,
+    content_types: Option<String>,
+    max_size: Option<ByteSize>,
+    default: Option<Lit>,
+    default_fn: Option<syn::Path>,
+    validate: Option<syn::Path>,
+    regex: Option<SpannedValue<String>>,
+    choices_fn: Option<syn::Path>,
+    max_count: Option<u64>,
+    min_count: Option<u64>,
     name: Option<String>,
     description: Option<String>,
+    name_localizations: Option<HashMap<String, String>>,
+    description_localizations: Option<HashMap<String, String>>,
 }
 
 pub struct CommandArg {
@@ -46,25 +202,189 @@ pub struct CommandArg {
     span: Span,
     ty: Type,
     options: CommandArgOptions,
+    /// Expression for the default value, if the argument has one
+    default: Option<Expr>,
+    /// Set for `Vec<T>` arguments registered as numbered options
+    variadic: Option<Variadic>,
+}
+
+/// A `Vec<T>` argument registered as `max` numbered options, of which the first `min` are required
+pub struct Variadic {
+    inner: Type,
+    min: u64,
+    max: u64,
+}
+
+/// A parameter of a command handler
+pub enum CommandParam {
+    /// The command context, either by value or by reference
+    Context { reference: bool },
+    /// A value filled by the framework through `FromContext`, marked with `#[extract]`
+    Extract(Box<Type>),
+    /// A struct implementing `CommandArguments`, marked with `#[flatten]`
+    Flatten(Box<Type>),
+    /// A Discord option
+    Argument(Box<CommandArg>),
 }
 
 pub struct CommandParameters {
-    pub args: Vec<CommandArg>,
+    pub params: Vec<CommandParam>,
     pub context: bool,
 }
 
+/// Checks if a type is ataraxy's `Context`, returning whether it is taken by reference.
+/// Matches `Context`, `&Context` and paths ending in `Context` (ie `ataraxy::Context`), aliased
+/// imports must be marked with `#[ctx]`. Serenity's context is rejected with an error pointing to
+/// the parameter, rather than being treated as an option
+fn context_kind(ty: &Type) -> Result<Option<bool>, MacroError> {
+    match ty {
+        Type::Reference(r) => match context_kind(&r.elem)? {
+            Some(false) if r.mutability.is_some() => Err(SynError(syn::Error::new(
+                ty.span(),
+                "Context must be taken by value or by shared reference",
+            ))),
+            Some(false) => Ok(Some(true)),
+            _ => Ok(None),
+        },
+        Type::Paren(p) => context_kind(&p.elem),
+        Type::Group(g) => context_kind(&g.elem),
+        Type::Path(p) if p.qself.is_none() => {
+            let segments = &p.path.segments;
+            let is_context = segments
+                .last()
+                .map(|s| s.ident == "Context" && s.arguments.is_empty())
+                .unwrap_or(false);
+            let from_serenity = segments
+                .first()
+                .map(|s| s.ident == "serenity")
+                .unwrap_or(false);
+            match (is_context, from_serenity) {
+                (true, true) => Err(SynError(syn::Error::new(
+                    ty.span(),
+                    "Command handlers take ataraxy's context, use `ataraxy::Context` instead of serenity's",
+                ))),
+                (true, false) => Ok(Some(false)),
+                _ => Ok(None),
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Checks if the last segment of a type path is `name` (ie `String` or `Option`)
+fn is_type(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Path(p) => p
+            .path
+            .segments
+            .last()
+            .map(|s| s.ident == name)
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Gets the expression for the default value of an argument from `default` or `default_fn`.
+/// String literals are parsed as expressions, except for `String` arguments where they are used as is
+fn default_value(ty: &Type, options: &CommandArgOptions) -> Result<Option<Expr>, MacroError> {
+    if options.default.is_none() && options.default_fn.is_none() {
+        return Ok(None);
+    }
+    if options.default.is_some() && options.default_fn.is_some() {
+        return Err(SynError(syn::Error::new(
+            ty.span(),
+            "Cannot have both `default` and `default_fn` on an option",
+        )));
+    }
+    if is_type(ty, "Option") {
+        return Err(SynError(syn::Error::new(
+            ty.span(),
+            "Options with a default value must not be wrapped in `Option`",
+        )));
+    }
+
+    if let Some(path) = &options.default_fn {
+        return Ok(Some(syn::parse_quote!(#path())));
+    }
+
+    Ok(match options.default.as_ref() {
+        Some(Lit::Str(s)) if is_type(ty, "String") => Some(syn::parse_quote!(#s.to_string())),
+        Some(Lit::Str(s)) => Some(s.parse::<Expr>().map_err(SynError)?),
+        Some(lit) => Some(syn::parse_quote!(#lit)),
+        None => None,
+    })
+}
+
+/// Gets `T` from `Vec<T>`
+fn vec_inner(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(p) => {
+            let segment = p.path.segments.last()?;
+            match &segment.arguments {
+                PathArguments::AngleBracketed(args) if segment.ident == "Vec" => {
+                    match args.args.first() {
+                        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Gets the numbered options of a `Vec<T>` argument from `max_count` and `min_count`
+fn variadic(ty: &Type, options: &CommandArgOptions) -> Result<Option<Variadic>, MacroError> {
+    let error = |message: &str| Err(SynError(syn::Error::new(ty.span(), message)));
+    let inner = match (vec_inner(ty), options.max_count) {
+        (Some(inner), Some(_)) => inner,
+        (Some(_), None) => {
+            return error("Vec options need a `max_count`, ie `#[option(max_count = 5)]`")
+        }
+        (None, _) if options.max_count.is_some() || options.min_count.is_some() => {
+            return error("`max_count` and `min_count` can only be used on Vec options")
+        }
+        (None, _) => return Ok(None),
+    };
+    let max = options.max_count.unwrap_or_default();
+    let min = options.min_count.unwrap_or(1);
+    if !(1..=25).contains(&max) {
+        return error(
+            "`max_count` must be between 1 and 25, the maximum number of options of a command",
+        );
+    }
+    if min > max {
+        return error("`min_count` must not be greater than `max_count`");
+    }
+    if options.default.is_some() || options.default_fn.is_some() {
+        return error(
+            "Vec options cannot have a default value, they are empty when no values are provided",
+        );
+    }
+    Ok(Some(Variadic {
+        inner: inner.clone(),
+        min,
+        max,
+    }))
+}
+
+fn has_marker(attrs: &[Attribute], marker: &str) -> bool {
+    attrs.iter().any(|a| a.path.is_ident(marker))
+}
+
 pub fn get_args(func: &ItemFn) -> Result<CommandParameters, MacroError> {
     if func.sig.inputs.is_empty() {
         return Ok(CommandParameters {
-            args: Vec::new(),
+            params: Vec::new(),
             context: false,
         });
     }
 
-    let mut args = Vec::new();
+    let mut params = Vec::new();
     let mut context = false;
 
-    for (i, arg) in func.sig.inputs.iter().enumerate() {
+    for arg in &func.sig.inputs {
         match arg {
             FnArg::Receiver(_) => {
                 return Err(SynError(syn::Error::new(
@@ -73,99 +393,310 @@ pub fn get_args(func: &ItemFn) -> Result<CommandParameters, MacroError> {
                 )))
             }
             Typed(t) => {
-                // TODO: Make context more robust
-                if let Type::Path(p) = &*t.ty {
-                    if p.path.is_ident("Context") {
-                        if context {
-                            return Err(SynError(syn::Error::new(
-                                arg.span(),
-                                "Cannot have multiple context arguments in a command",
-                            )));
-                        }
-                        if i != 0 {
+                let context_param = if has_marker(&t.attrs, "ctx") {
+                    match &*t.ty {
+                        Type::Reference(r) if r.mutability.is_some() => {
                             return Err(SynError(syn::Error::new(
-                                arg.span(),
-                                "Context must be first argument in a command",
-                            )));
+                                t.ty.span(),
+                                "Context must be taken by value or by shared reference",
+                            )))
                         }
-                        context = true;
-                        continue;
+                        Type::Reference(_) => Some(true),
+                        _ => Some(false),
+                    }
+                } else {
+                    context_kind(&t.ty)?
+                };
+
+                if let Some(reference) = context_param {
+                    if context {
+                        return Err(SynError(syn::Error::new(
+                            arg.span(),
+                            "Cannot have multiple context arguments in a command",
+                        )));
                     }
+                    context = true;
+                    params.push(CommandParam::Context { reference });
+                    continue;
+                }
+
+                if has_marker(&t.attrs, "extract") {
+                    params.push(CommandParam::Extract(t.ty.clone()));
+                    continue;
                 }
 
-                let options = <CommandArgOptions as darling::FromMeta>::from_list(
-                    &t.attrs
-                        .iter()
-                        .filter_map(|a| {
-                            a.parse_meta().ok().and_then(|m| {
-                                if let Meta::List(l) = m {
-                                    if l.path.is_ident("option") {
-                                        Some(l.nested)
-                                    } else {
-                                        None
-                                    }
-                                } else {
-                                    None
-                                }
-                            })
-                        })
-                        .flatten()
-                        .collect::<Vec<_>>(),
-                )
-                .map_err(DarlingError)?;
+                if has_marker(&t.attrs, "flatten") {
+                    params.push(CommandParam::Flatten(t.ty.clone()));
+                    continue;
+                }
 
                 if let Pat::Ident(id) = &*t.pat {
-                    args.push(CommandArg {
-                        name: id.ident.to_string(),
-                        span: t.span(),
-                        ty: *t.ty.clone(),
-                        options,
-                    })
+                    params.push(CommandParam::Argument(Box::new(CommandArg::new(
+                        id.ident.to_string(),
+                        t.span(),
+                        &t.ty,
+                        &t.attrs,
+                    )?)))
                 }
             }
         }
     }
-    Ok(CommandParameters { args, context })
+    Ok(CommandParameters { params, context })
+}
+
+/// Expression for the signatures of the options of a list of parameters, in order.
+/// Flattened parameters contribute all of their options
+pub fn signatures(params: &[CommandParam]) -> Result<TokenStream, MacroError> {
+    let mut groups = Vec::new();
+    for param in params {
+        match param {
+            CommandParam::Argument(arg) => {
+                let signatures = arg.as_signatures()?;
+                groups.push(quote! { ::std::vec![#(#signatures),*] });
+            }
+            CommandParam::Flatten(ty) => groups.push(quote! {
+                <#ty as ::ataraxy::framework::command::argument::CommandArguments>::signatures()
+            }),
+            _ => (),
+        }
+    }
+    Ok(quote! {
+        <[::std::vec::Vec<::ataraxy::framework::command::argument::CommandArgumentSignature>]>::concat(&[#(#groups),*])
+    })
+}
+
+/// Checks the options of a list of parameters fit in a command. Options of flattened parameters
+/// are only known at runtime, and are checked when the command is registered
+pub fn check_option_count(
+    params: &[CommandParam],
+    name: &str,
+    span: Span,
+) -> Result<(), MacroError> {
+    let count: u64 = params
+        .iter()
+        .map(|param| match param {
+            CommandParam::Argument(arg) => arg.variadic.as_ref().map_or(1, |v| v.max),
+            _ => 0,
+        })
+        .sum();
+    if count > MAX_OPTIONS as u64 {
+        return Err(SynError(syn::Error::new(
+            span,
+            format!(
+                "{} has {} options, Discord accepts at most {}",
+                name, count, MAX_OPTIONS
+            ),
+        )));
+    }
+    Ok(())
 }
 
 impl CommandParameters {
-    pub fn as_signature(&self) -> TokenStream {
+    pub fn as_signature(&self) -> Result<TokenStream, MacroError> {
         let context = self.context;
 
-        let args: Vec<TokenStream> = self
-            .args
-            .iter()
-            .map(|arg| arg.as_signature())
-            .collect();
+        let arguments = signatures(&self.params)?;
 
-        quote! {
+        Ok(quote! {
             ::ataraxy::framework::command::argument::CommandSignature {
                 context: #context,
-                arguments: Vec::from([#(#args),*])
+                arguments: #arguments
             }
-        }
+        })
     }
 }
 
 impl CommandArg {
-    pub fn as_signature(&self) -> TokenStream {
+    /// Parses an argument from its `#[option(...)]` attributes
+    pub fn new(
+        name: String,
+        span: Span,
+        ty: &Type,
+        attrs: &[Attribute],
+    ) -> Result<Self, MacroError> {
+        let options = <CommandArgOptions as darling::FromMeta>::from_list(
+            &attrs
+                .iter()
+                .filter_map(|a| {
+                    a.parse_meta().ok().and_then(|m| {
+                        if let Meta::List(l) = m {
+                            if l.path.is_ident("option") {
+                                Some(l.nested)
+                            } else {
+                                None
+                            }
+                        } else {
+                            None
+                        }
+                    })
+                })
+                .flatten()
+                .collect::<Vec<_>>(),
+        )
+        .map_err(DarlingError)?;
+
+        let default = default_value(ty, &options)?;
+        let variadic = variadic(ty, &options)?;
+        check_bounds(ty, &options)?;
+        if let Some(pattern) = &options.regex {
+            regex::Regex::new(pattern).map_err(|e| {
+                SynError(syn::Error::new(
+                    pattern.span(),
+                    format!("Invalid regex in option: {}", e),
+                ))
+            })?;
+        }
+
+        Ok(Self {
+            name,
+            span,
+            ty: ty.clone(),
+            options,
+            default,
+            variadic,
+        })
+    }
+
+    /// Sets the description if none was given with `#[option(description = "...")]`
+    pub fn describe(&mut self, description: Option<String>) {
+        if self.options.description.is_none() {
+            self.options.description = description;
+        }
+    }
+
+    /// Signatures of the options of the argument, one for each numbered option of a `Vec<T>`.
+    /// Defaults given with `default` are appended to the description, `default_fn` is only
+    /// evaluated when the command runs so its value cannot be shown
+    pub fn as_signatures(&self) -> Result<Vec<TokenStream>, MacroError> {
         let ty = &self.ty;
         let name = self.options.name.as_ref().unwrap_or(&self.name);
-        let description = self.options.description.as_ref().unwrap_or(name);
+        let mut description = self.options.description.as_ref().unwrap_or(name).clone();
+
+        if let Some(variadic) = &self.variadic {
+            let inner = &variadic.inner;
+            return (1..=variadic.max)
+                .map(|i| {
+                    let argument = if i <= variadic.min {
+                        quote! { <#inner as ::ataraxy::framework::command::argument::AsCommandArgument>::command_argument_type() }
+                    } else {
+                        quote! { <::core::option::Option<#inner> as ::ataraxy::framework::command::argument::AsCommandArgument>::command_argument_type() }
+                    };
+                    self.signature(&format!("{}{}", name, i), &description, argument, &i.to_string())
+                })
+                .collect::<Result<_, _>>();
+        }
+
+        let argument = match &self.default {
+            Some(_) => {
+                if let Some(default) = &self.options.default {
+                    let default = match default {
+                        Lit::Str(s) => s.value(),
+                        lit => lit.to_token_stream().to_string(),
+                    };
+                    description = format!("{} (default: {})", description, default);
+                }
+                quote! { <::core::option::Option<#ty> as ::ataraxy::framework::command::argument::AsCommandArgument>::command_argument_type() }
+            }
+            None => {
+                quote! { <#ty as ::ataraxy::framework::command::argument::AsCommandArgument>::command_argument_type() }
+            }
+        };
+        Ok(vec![self.signature(name, &description, argument, "")?])
+    }
+
+    /// Signature of one option, `suffix` is appended to the translated names of numbered options
+    fn signature(
+        &self,
+        name: &str,
+        description: &str,
+        argument: TokenStream,
+        suffix: &str,
+    ) -> Result<TokenStream, MacroError> {
+        if description.chars().count() > MAX_DESCRIPTION_LEN {
+            return Err(SynError(syn::Error::new(
+                self.span,
+                format!(
+                    "Option description `{}` is longer than {} characters",
+                    description, MAX_DESCRIPTION_LEN
+                ),
+            )));
+        }
         let options = self.options_as_tokens();
-        quote_spanned! { self.span =>
+        let name_localizations = quote_localizations(&self.options.name_localizations, suffix);
+        let description_localizations =
+            quote_localizations(&self.options.description_localizations, "");
+        Ok(quote_spanned! { self.span =>
             ::ataraxy::framework::command::argument::CommandArgumentSignature {
                 name: #name.to_string(),
                 description: #description.to_string(),
-                argument: <#ty as ::ataraxy::framework::command::argument::AsCommandArgument>::command_argument_type(),
+                name_localizations: #name_localizations,
+                description_localizations: #description_localizations,
+                choice_localizations: ::std::collections::HashMap::new(),
+                argument: #argument,
                 options: #options
             }
+        })
+    }
+
+    /// Expression getting the value of the argument from the argument list `args`,
+    /// where `ctx` is an expression for a `&Context`
+    pub fn as_value(&self, ctx: &TokenStream) -> TokenStream {
+        if let Some(variadic) = &self.variadic {
+            let inner = &variadic.inner;
+            let (min, max) = (variadic.min as usize, variadic.max as usize);
+            // Validators run on each value
+            let validator = match &self.options.validate {
+                Some(path) => quote_spanned! { self.span =>
+                    |value: &#inner| #path(value).map_err(|e| ::std::string::ToString::to_string(&e))
+                },
+                None => quote! { |_: &#inner| ::core::result::Result::Ok(()) },
+            };
+            return quote_spanned! { self.span =>
+                args.variadic::<#inner, _>(#ctx, #max, #min, #validator).await?
+            };
+        }
+
+        let ty = &self.ty;
+        let arg_ty = match &self.default {
+            Some(_) => quote! { ::core::option::Option<#ty> },
+            None => quote! { #ty },
+        };
+
+        // Validators only run on values that were provided
+        let validator = match &self.options.validate {
+            Some(path) if self.default.is_some() || is_type(ty, "Option") => {
+                quote_spanned! { self.span =>
+                    |value: &#arg_ty| match value {
+                        ::core::option::Option::Some(value) => #path(value).map_err(|e| ::std::string::ToString::to_string(&e)),
+                        ::core::option::Option::None => ::core::result::Result::Ok(()),
+                    }
+                }
+            }
+            Some(path) => quote_spanned! { self.span =>
+                |value: &#arg_ty| #path(value).map_err(|e| ::std::string::ToString::to_string(&e))
+            },
+            None => quote! { |_| ::core::result::Result::Ok(()) },
+        };
+
+        match &self.default {
+            Some(default) => quote_spanned! { self.span =>
+                args.arg_with::<#arg_ty, _>(#ctx, #validator)
+                    .await?
+                    .unwrap_or_else(|| #default)
+            },
+            None => quote_spanned! { self.span =>
+                args.arg_with::<#arg_ty, _>(#ctx, #validator).await?
+            },
         }
     }
 
     fn options_as_tokens(&self) -> TokenStream {
-        let min = quote_option(&self.options.min);
-        let max = quote_option(&self.options.max);
+        let min = quote_option(&Bound::number(&self.options.min));
+        let max = quote_option(&Bound::number(&self.options.max));
+        let min_text =
+            quote_option(&Bound::text(&self.options.min).map(|t| quote!(#t.to_string())));
+        let max_text =
+            quote_option(&Bound::text(&self.options.max).map(|t| quote!(#t.to_string())));
         let min_len = quote_option(&self.options.min_len);
         let max_len = quote_option(&self.options.max_len);
         let channel_type = quote_option(&self.options.channel_type.clone().map(|cs| {
@@ -199,13 +730,41 @@ impl CommandArg {
                 }
             }).collect::<Vec<TokenStream>>())
         }));
+        let content_types = quote_option(&self.options.content_types.as_ref().map(|c| {
+            let types: Vec<TokenStream> = c
+                .split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(|t| quote!(#t.to_string()))
+                .collect();
+            quote_vec(&types)
+        }));
+        let max_size = quote_option(&self.options.max_size.map(|s| s.0));
+        // The pattern was compiled by the macro, and is compiled once more when the command is built
+        let regex = quote_option(&self.options.regex.as_ref().map(|r| {
+            let r = r.as_str();
+            quote! { ::ataraxy::regex::Regex::new(#r).expect("Regex was checked by the option macro") }
+        }));
+        // Names and values are converted with `Into`, so the function can return ie `Vec<(&str, i32)>`
+        let choices = quote_option(&self.options.choices_fn.as_ref().map(|path| {
+            let name = self.options.name.as_ref().unwrap_or(&self.name);
+            quote_spanned! { self.span =>
+                ::ataraxy::framework::command::argument::collect_choices(#name, #path())
+            }
+        }));
         quote! {
             ::ataraxy::framework::command::argument::CommandArgumentOptions {
                 min: #min,
                 max: #max,
+                min_text: #min_text,
+                max_text: #max_text,
                 min_len: #min_len,
                 max_len: #max_len,
                 channel_type: #channel_type,
+                content_types: #content_types,
+                max_size: #max_size,
+                regex: #regex,
+                choices: #choices,
             }
         }
     }
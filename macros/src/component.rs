@@ -0,0 +1,183 @@
+use crate::utils::MacroError;
+use crate::utils::MacroError::*;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{FnArg, Ident, ItemFn, LitStr, Pat, Type};
+
+/// Names of the captures of a custom id pattern, checked with the same rules as
+/// `ComponentPattern::parse` so patterns accepted here never fail at runtime
+fn pattern_captures(pattern: &str) -> Result<Vec<String>, String> {
+    let mut captures = Vec::new();
+    let mut rest = pattern;
+    let mut previous_capture = false;
+    while !rest.is_empty() {
+        match rest.find('{') {
+            Some(0) => {
+                let end = rest
+                    .find('}')
+                    .ok_or_else(|| "Unclosed capture in pattern".to_string())?;
+                let name = &rest[1..end];
+                if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(format!("Invalid capture name `{}`", name));
+                }
+                if previous_capture {
+                    return Err("Captures must be separated by literal text".to_string());
+                }
+                if captures.iter().any(|c| c == name) {
+                    return Err(format!("Duplicate capture `{}`", name));
+                }
+                captures.push(name.to_string());
+                previous_capture = true;
+                rest = &rest[end + 1..];
+            }
+            start => {
+                let end = start.unwrap_or(rest.len());
+                if rest[..end].contains('}') {
+                    return Err("Unopened capture in pattern".to_string());
+                }
+                previous_capture = false;
+                rest = &rest[end..];
+            }
+        }
+    }
+    Ok(captures)
+}
+
+/// Checks if a type is `ComponentContext`, returning whether it is taken by reference
+fn context_kind(ty: &Type) -> Option<bool> {
+    match ty {
+        Type::Reference(r) if r.mutability.is_none() => context_kind(&r.elem).map(|_| true),
+        Type::Paren(p) => context_kind(&p.elem),
+        Type::Group(g) => context_kind(&g.elem),
+        Type::Path(p) if p.qself.is_none() => p
+            .path
+            .segments
+            .last()
+            .filter(|s| s.ident == "ComponentContext" && s.arguments.is_empty())
+            .map(|_| false),
+        _ => None,
+    }
+}
+
+pub fn component(pattern: LitStr, function: ItemFn) -> Result<TokenStream, MacroError> {
+    let captures = pattern_captures(&pattern.value())
+        .map_err(|e| SynError(syn::Error::new(pattern.span(), e)))?;
+
+    let func_name = &function.sig.ident;
+    let visibility = &function.vis;
+    if function.sig.asyncness.is_none() {
+        return Err(SynError(syn::Error::new(
+            function.sig.ident.span(),
+            "Component handler must be marked as async",
+        )));
+    }
+
+    let mut inner_function = function.clone();
+    inner_function.sig.ident = syn::parse_quote! { inner };
+    for i in &mut inner_function.sig.inputs {
+        if let FnArg::Typed(t) = i {
+            t.attrs = vec![];
+        }
+    }
+
+    let mut context = false;
+    let mut args = Vec::new();
+    let mut idents: Vec<Ident> = Vec::new();
+    let mut values = Vec::new();
+    for arg in &function.sig.inputs {
+        let t = match arg {
+            FnArg::Receiver(_) => {
+                return Err(SynError(syn::Error::new(
+                    arg.span(),
+                    "Cannot have a self argument in a component handler",
+                )))
+            }
+            FnArg::Typed(t) => t,
+        };
+
+        let context_param = if t.attrs.iter().any(|a| a.path.is_ident("ctx")) {
+            Some(matches!(&*t.ty, Type::Reference(_)))
+        } else {
+            context_kind(&t.ty)
+        };
+        if let Some(reference) = context_param {
+            if context {
+                return Err(SynError(syn::Error::new(
+                    arg.span(),
+                    "Cannot have multiple context arguments in a component handler",
+                )));
+            }
+            context = true;
+            args.push(if reference {
+                quote!(&ctx)
+            } else {
+                quote!(ctx.clone())
+            });
+            continue;
+        }
+
+        let ident = match &*t.pat {
+            Pat::Ident(id) => id.ident.clone(),
+            _ => {
+                return Err(SynError(syn::Error::new(
+                    t.pat.span(),
+                    "Component handler parameters must be identifiers",
+                )))
+            }
+        };
+        let ty = &t.ty;
+        args.push(quote!(#ident));
+        idents.push(ident.clone());
+
+        // Payloads are decoded from the whole custom id rather than from a capture
+        if t.attrs.iter().any(|a| a.path.is_ident("payload")) {
+            values.push(quote! {
+                ctx.payload::<#ty>()?
+            });
+            continue;
+        }
+
+        let name = ident.to_string();
+        let name = name.strip_prefix("r#").unwrap_or(&name).to_string();
+        if !captures.contains(&name) {
+            return Err(SynError(syn::Error::new(
+                ident.span(),
+                format!(
+                    "`{}` is not a capture of `{}`, expected one of: {}",
+                    name,
+                    pattern.value(),
+                    captures.join(", ")
+                ),
+            )));
+        }
+        values.push(quote! {
+            ::ataraxy::framework::component::parse_capture::<#ty>(captures, #name)?
+        });
+    }
+
+    Ok(quote! {
+        #visibility fn #func_name() -> ::ataraxy::Component {
+            #inner_function
+
+            ::ataraxy::Component {
+                pattern: ::ataraxy::framework::component::ComponentPattern::parse(#pattern)
+                    .expect("Pattern was checked by the component macro"),
+                action: ::ataraxy::framework::component::ComponentHandler(|ctx, captures| {
+                    let parsed = (|| {
+                        ::core::result::Result::Ok::<_, ::ataraxy::framework::command::argument::ArgumentError>((
+                            #(#values,)*
+                        ))
+                    })();
+
+                    Box::pin(async move {
+                        let (#(#idents,)*) = parsed?;
+                        inner(#(#args),*).await;
+                        ::core::result::Result::Ok::<(), ::ataraxy::framework::command::argument::ArgumentError>(())
+                    })
+                }),
+            }
+        }
+    }
+    .into())
+}
@@ -4,7 +4,10 @@ use syn::parse_quote;
 use syn::spanned::Spanned;
 use utils::MacroError;
 
+mod arguments;
 mod command;
+mod component;
+mod modal;
 mod utils;
 
 /// Procedural macro used to transform functions into commands
@@ -29,6 +32,48 @@ mod utils;
 ///     ctx.reply_ephemeral("Sent message").await;
 /// }
 /// ```
+///
+/// Options that are not wrapped in `Option` can be made optional with a default value, using
+/// `#[option(default = "10")]` or `#[option(default_fn = "path::to::fn")]`. For `String` options
+/// the default is used as is, for other types it is parsed as an expression.
+///
+/// Options can be validated after conversion with `#[option(validate = "path::to::fn")]`, where the
+/// function takes a reference to the value and returns a `Result<(), E: Display>`, and string options
+/// can be matched against a pattern with `#[option(regex = "^[a-z]+$")]`. Failures are reported back
+/// to the user ephemerally.
+///
+/// Choices can be loaded when commands are registered with `#[option(choices_fn = "path::to::fn")]`,
+/// where the function returns `(name, value)` pairs such as `Vec<(String, String)>` or
+/// `Vec<(&str, i64)>`. The function is called once when the command is built, which panics if it
+/// returns more than the 25 choices Discord allows. Incoming values that are not one of the choices
+/// are rejected.
+///
+/// Names and descriptions of commands and options can be translated with
+/// `name_localizations(de = "...", es_ES = "...")` and `description_localizations(...)`, in
+/// `#[command(...)]` or `#[option(...)]`. Locales are written with `_` instead of `-`.
+///
+/// `min` and `max` take numbers for numeric options, or strings for types parsed from text, such
+/// as `#[option(min = "1m", max = "28d")]` on a `Duration` (see `ataraxy::framework::command::time`).
+///
+/// `Vec<T>` options are registered as numbered options with `#[option(max_count = 5)]`
+/// (`users1` to `users5`), collecting the values that were provided. The first `min_count`
+/// options are required, 1 by default, and validators run on each value.
+///
+/// Parameters marked with `#[flatten]` take a struct deriving `CommandArguments`, whose fields
+/// are registered as individual options.
+///
+/// The context may be taken by value or by reference in any position. Aliased imports of
+/// `Context` must be marked with `#[ctx]`.
+///
+/// Parameters marked with `#[extract]` are not registered as options, but filled by the
+/// framework through `FromContext`:
+/// ```rust, ignore
+/// /// Shows the configured prefix
+/// #[command]
+/// async fn prefix(ctx: Context, #[extract] config: Data<Config>, #[extract] author: Author) {
+///     ctx.reply(format!("{}: {}", author.name, config.prefix)).await;
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn command(args: TokenStream, function: TokenStream) -> TokenStream {
     let args = syn::parse_macro_input!(args as Vec<syn::NestedMeta>);
@@ -48,6 +93,99 @@ pub fn command(args: TokenStream, function: TokenStream) -> TokenStream {
     }
 }
 
+/// Derives `CommandArguments` for a struct, registering each field as an option of the commands
+/// that take it with `#[flatten]`. Fields take the same `#[option(...)]` attributes as command
+/// parameters, doc comments are used as descriptions, and nested structs can be flattened too:
+/// ```rust, ignore
+/// #[derive(CommandArguments)]
+/// struct BanArgs {
+///     /// Member to ban
+///     user: User,
+///     /// Reason shown in the audit log
+///     reason: Option<String>,
+///     #[option(default = "0", max = 7)]
+///     delete_days: u8,
+/// }
+///
+/// /// Bans a member
+/// #[command]
+/// async fn ban(ctx: Context, #[flatten] args: BanArgs) {
+///     // ...
+/// }
+/// ```
+#[proc_macro_derive(CommandArguments, attributes(option, flatten))]
+pub fn derive_command_arguments(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match arguments::derive(input) {
+        Ok(x) => x,
+        Err(e) => match e {
+            MacroError::SynError(e) => e.to_compile_error().into(),
+            MacroError::DarlingError(e) => e.write_errors().into(),
+        },
+    }
+}
+
+/// Derives `Modal` for a struct, each field becoming a text input shown with
+/// `Context::show_modal`. Fields wrapped in `Option` are optional, other fields are required,
+/// and values are parsed with `FromStr`. Labels default to the doc comment of the field.
+/// ```rust, ignore
+/// #[derive(Modal)]
+/// #[modal(title = "Report a bug")]
+/// struct Report {
+///     /// Title
+///     #[input(max_len = 100)]
+///     title: String,
+///     #[input(label = "What happened?", style = "paragraph", min_len = 20)]
+///     description: String,
+///     #[input(label = "Version", placeholder = "1.0.0")]
+///     version: Option<String>,
+/// }
+/// ```
+#[proc_macro_derive(Modal, attributes(modal, input))]
+pub fn derive_modal(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match modal::derive(input) {
+        Ok(x) => x,
+        Err(e) => match e {
+            MacroError::SynError(e) => e.to_compile_error().into(),
+            MacroError::DarlingError(e) => e.write_errors().into(),
+        },
+    }
+}
+
+/// Procedural macro used to transform functions into handlers for buttons and select menus,
+/// producing functions which can be passed to [`Framework::component`](ataraxy::Framework::component).
+///
+/// The pattern is matched against the custom id of the component, with `{name}` capturing text up
+/// to the next literal part. Parameters are filled from the captures of the same name, parsed with
+/// `FromStr`, and the `ComponentContext` may be taken by value or by reference in any position.
+/// ```rust, ignore
+/// #[component("vote:{poll}:{choice}")]
+/// async fn vote(ctx: ComponentContext, poll: u64, choice: String) {
+///     ctx.reply_ephemeral(format!("Voted for {} in poll {}", choice, poll)).await.ok();
+/// }
+///
+/// let framework = Framework::new().component(vote);
+/// ```
+///
+/// A parameter marked with `#[payload]` is decoded from a signed custom id created with
+/// `encode_custom_id`, see `ataraxy::framework::custom_id`.
+#[proc_macro_attribute]
+pub fn component(args: TokenStream, function: TokenStream) -> TokenStream {
+    let pattern = syn::parse_macro_input!(args as syn::LitStr);
+    let function = syn::parse_macro_input!(function as syn::ItemFn);
+
+    match component::component(pattern, function) {
+        Ok(x) => x,
+        Err(e) => match e {
+            MacroError::SynError(e) => e.to_compile_error().into(),
+            MacroError::DarlingError(e) => e.write_errors().into(),
+        },
+    }
+}
+
 /// For use with command addition for IDEs that do not support proc macros changing
 /// function signatures (looking at you clion)
 #[proc_macro_attribute]
@@ -0,0 +1,189 @@
+use crate::command::extract_doc_comments;
+use crate::utils::MacroError;
+use crate::utils::MacroError::*;
+use crate::utils::{option_inner, quote_option};
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Attribute, Data, DeriveInput, Fields, Meta};
+
+/// Discord limits for modals
+const MAX_FIELDS: usize = 5;
+const MAX_LABEL_LEN: usize = 45;
+const MAX_INPUT_LEN: u64 = 4000;
+
+#[derive(Default, Debug, darling::FromMeta)]
+#[darling(default)]
+struct ModalArgs {
+    title: Option<String>,
+}
+
+#[derive(Default, Debug, darling::FromMeta)]
+#[darling(default)]
+struct InputArgs {
+    label: Option<String>,
+    style: Option<String>,
+    min_len: Option<u64>,
+    max_len: Option<u64>,
+    placeholder: Option<String>,
+    value: Option<String>,
+}
+
+/// Collects the contents of every `#[name(...)]` attribute
+fn attribute_list(attrs: &[Attribute], name: &str) -> Vec<syn::NestedMeta> {
+    attrs
+        .iter()
+        .filter_map(|a| match a.parse_meta() {
+            Ok(Meta::List(l)) if l.path.is_ident(name) => Some(l.nested),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// Implements `Modal` for a struct with named fields, each field becoming a text input
+pub fn derive(input: DeriveInput) -> Result<TokenStream, MacroError> {
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(SynError(syn::Error::new(
+                    input.span(),
+                    "Modal can only be derived for structs with named fields",
+                )))
+            }
+        },
+        _ => {
+            return Err(SynError(syn::Error::new(
+                input.span(),
+                "Modal can only be derived for structs",
+            )))
+        }
+    };
+    if fields.is_empty() || fields.len() > MAX_FIELDS {
+        return Err(SynError(syn::Error::new(
+            input.span(),
+            format!("Modals must have between 1 and {} fields", MAX_FIELDS),
+        )));
+    }
+
+    let args = <ModalArgs as darling::FromMeta>::from_list(&attribute_list(&input.attrs, "modal"))
+        .map_err(DarlingError)?;
+    let title = args.title.unwrap_or_else(|| input.ident.to_string());
+    if title.is_empty() || title.chars().count() > MAX_LABEL_LEN {
+        return Err(SynError(syn::Error::new(
+            input.span(),
+            format!(
+                "Modal titles must be between 1 and {} characters",
+                MAX_LABEL_LEN
+            ),
+        )));
+    }
+
+    let mut inputs = Vec::new();
+    let mut values = Vec::new();
+    for field in fields {
+        let ident = field.ident.clone().expect("Named fields have identifiers");
+        let custom_id = ident.to_string();
+        let custom_id = custom_id
+            .strip_prefix("r#")
+            .unwrap_or(&custom_id)
+            .to_string();
+        let args =
+            <InputArgs as darling::FromMeta>::from_list(&attribute_list(&field.attrs, "input"))
+                .map_err(DarlingError)?;
+        let error = |message: String| Err(SynError(syn::Error::new(field.span(), message)));
+
+        let label = match args.label {
+            Some(label) => label,
+            None => extract_doc_comments(&field.attrs)
+                .map(|d| d.trim().to_string())
+                .unwrap_or_else(|| custom_id.clone()),
+        };
+        if label.is_empty() || label.chars().count() > MAX_LABEL_LEN {
+            return error(format!(
+                "Input labels must be between 1 and {} characters",
+                MAX_LABEL_LEN
+            ));
+        }
+
+        let style = match args.style.as_deref() {
+            None | Some("short") => quote!(::ataraxy::framework::modal::InputStyle::Short),
+            Some("paragraph") => quote!(::ataraxy::framework::modal::InputStyle::Paragraph),
+            Some(style) => {
+                return error(format!(
+                    "Unknown input style `{}`, expected `short` or `paragraph`",
+                    style
+                ))
+            }
+        };
+
+        match (args.min_len, args.max_len) {
+            (Some(min), _) if min > MAX_INPUT_LEN => {
+                return error(format!("`min_len` must be at most {}", MAX_INPUT_LEN))
+            }
+            (_, Some(max)) if max == 0 || max > MAX_INPUT_LEN => {
+                return error(format!("`max_len` must be between 1 and {}", MAX_INPUT_LEN))
+            }
+            (Some(min), Some(max)) if min > max => {
+                return error("`min_len` must not be greater than `max_len`".to_string())
+            }
+            _ => (),
+        }
+
+        let optional = option_inner(&field.ty);
+        let required = optional.is_none();
+        values.push(match optional {
+            Some(inner) => quote! {
+                #ident: values.parse_optional::<#inner>(#custom_id, #label)?
+            },
+            None => {
+                let ty = &field.ty;
+                quote! {
+                    #ident: values.parse::<#ty>(#custom_id, #label)?
+                }
+            }
+        });
+
+        let min_len = quote_option(&args.min_len);
+        let max_len = quote_option(&args.max_len);
+        let placeholder = quote_option(&args.placeholder.map(|p| quote!(#p.to_string())));
+        let value = quote_option(&args.value.map(|v| quote!(#v.to_string())));
+        inputs.push(quote! {
+            ::ataraxy::framework::modal::ModalField {
+                custom_id: #custom_id.to_string(),
+                label: #label.to_string(),
+                style: #style,
+                min_len: #min_len,
+                max_len: #max_len,
+                required: #required,
+                placeholder: #placeholder,
+                value: #value,
+            }
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::ataraxy::framework::modal::Modal for #name #ty_generics #where_clause {
+            fn title() -> ::std::string::String {
+                #title.to_string()
+            }
+
+            fn fields() -> ::std::vec::Vec<::ataraxy::framework::modal::ModalField> {
+                ::std::vec![#(#inputs),*]
+            }
+
+            fn from_values(
+                values: &::ataraxy::framework::modal::ModalValues,
+            ) -> ::core::result::Result<Self, ::ataraxy::framework::command::argument::ArgumentError> {
+                ::core::result::Result::Ok(Self {
+                    #(#values),*
+                })
+            }
+        }
+    }
+    .into())
+}
@@ -1,6 +1,7 @@
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
-use syn::Lit;
+use std::collections::HashMap;
+use syn::{GenericArgument, Lit, PathArguments, Type};
 
 /// Struct to parse a list of values from an attribute in darling: `#[multiple("item1", "item2")]`
 #[derive(Debug, Clone, Default)]
@@ -34,8 +35,47 @@ pub fn quote_option<T: ToTokens>(opt: &Option<T>) -> TokenStream {
     }
 }
 
+/// Quotes translations from a macro attribute as a `HashMap` of Discord locales,
+/// with the identifiers converted to locales (ie `es_ES` to `es-ES`) and `suffix` appended to each text
+pub fn quote_localizations(
+    localizations: &Option<HashMap<String, String>>,
+    suffix: &str,
+) -> TokenStream {
+    let mut entries: Vec<(String, String)> = localizations
+        .iter()
+        .flatten()
+        .map(|(locale, text)| (locale.replace('_', "-"), format!("{}{}", text, suffix)))
+        .collect();
+    entries.sort();
+    let (locales, texts): (Vec<String>, Vec<String>) = entries.into_iter().unzip();
+    quote! {
+        <::std::collections::HashMap<::std::string::String, ::std::string::String> as ::core::iter::FromIterator<_>>::from_iter(
+            ::std::vec![#((#locales.to_string(), #texts.to_string())),*]
+        )
+    }
+}
+
 /// Enum to wrap syn and darling errors
 pub enum MacroError {
     DarlingError(darling::Error),
     SynError(syn::Error),
 }
+
+/// Gets `T` from `Option<T>`
+pub fn option_inner(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(p) => {
+            let segment = p.path.segments.last()?;
+            match &segment.arguments {
+                PathArguments::AngleBracketed(args) if segment.ident == "Option" => {
+                    match args.args.first() {
+                        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,224 @@
+use crate::framework::component::ComponentContext;
+use crate::framework::custom_id::CustomIdCodec;
+use crate::framework::DataMap;
+use serenity::model::id::{InteractionId, UserId};
+use serenity::model::interactions::message_component::MessageComponentInteraction;
+use serenity::model::interactions::modal::ModalSubmitInteraction;
+use serenity::prelude::Context as SerenityContext;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// Filter applied to component interactions before handing them to a collector
+pub(crate) type ComponentFilter = Box<dyn Fn(&MessageComponentInteraction) -> bool + Send + Sync>;
+
+/// Outcome of handing an interaction to the collectors
+pub(crate) enum Collected<T> {
+    /// A running command took the interaction
+    Taken,
+    /// No command is waiting for the interaction, which is given back
+    Unclaimed(T),
+}
+
+/// A failed send gives the interaction back, ie when the command stopped waiting
+impl<T> From<Result<(), T>> for Collected<T> {
+    fn from(sent: Result<(), T>) -> Self {
+        match sent {
+            Ok(()) => Collected::Taken,
+            Err(interaction) => Collected::Unclaimed(interaction),
+        }
+    }
+}
+
+/// A command waiting for components of its reply to be used
+struct ComponentWaiter {
+    id: u64,
+    /// The command interaction whose reply holds the components
+    interaction: InteractionId,
+    user: UserId,
+    filter: ComponentFilter,
+    /// Whether the waiter is removed after the first interaction
+    once: bool,
+    sender: mpsc::UnboundedSender<MessageComponentInteraction>,
+}
+
+/// Interactions awaited by running commands, fed by the framework's `interaction_create`.
+/// Shared by the framework with every command context
+#[derive(Default)]
+pub(crate) struct Collectors {
+    modals: Mutex<HashMap<String, (UserId, oneshot::Sender<ModalSubmitInteraction>)>>,
+    components: Mutex<Vec<ComponentWaiter>>,
+    next_id: AtomicU64,
+}
+
+impl Collectors {
+    /// Waits for the submission of the modal with the given custom id by `user`
+    pub(crate) fn modal(
+        &self,
+        custom_id: String,
+        user: UserId,
+    ) -> oneshot::Receiver<ModalSubmitInteraction> {
+        let (sender, receiver) = oneshot::channel();
+        self.modals
+            .lock()
+            .unwrap()
+            .insert(custom_id, (user, sender));
+        receiver
+    }
+
+    /// Stops waiting for a modal, ie after a timeout
+    pub(crate) fn cancel_modal(&self, custom_id: &str) {
+        self.modals.lock().unwrap().remove(custom_id);
+    }
+
+    /// Hands a modal submission to the command waiting for it,
+    /// giving it back if nothing is waiting (ie the modal expired)
+    pub(crate) fn submit_modal(
+        &self,
+        interaction: ModalSubmitInteraction,
+    ) -> Collected<ModalSubmitInteraction> {
+        let mut modals = self.modals.lock().unwrap();
+        match modals.get(&interaction.data.custom_id) {
+            Some((user, _)) if *user == interaction.user.id => {
+                let (_, sender) = modals
+                    .remove(&interaction.data.custom_id)
+                    .expect("Modal is waiting");
+                sender.send(interaction).into()
+            }
+            _ => Collected::Unclaimed(interaction),
+        }
+    }
+
+    /// Waits for `user` to use components on the reply to the command `interaction`, only for
+    /// the first one if `once` is set. Returns an id to cancel the waiter with
+    pub(crate) fn component(
+        &self,
+        interaction: InteractionId,
+        user: UserId,
+        filter: ComponentFilter,
+        once: bool,
+    ) -> (u64, mpsc::UnboundedReceiver<MessageComponentInteraction>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.components.lock().unwrap().push(ComponentWaiter {
+            id,
+            interaction,
+            user,
+            filter,
+            once,
+            sender,
+        });
+        (id, receiver)
+    }
+
+    /// Stops waiting for a component, ie after a timeout
+    pub(crate) fn cancel_component(&self, id: u64) {
+        self.components.lock().unwrap().retain(|c| c.id != id);
+    }
+
+    /// Hands a component interaction to the first command waiting for it, giving it back if
+    /// nothing is waiting. Components of messages that are not a reply to a command are never
+    /// collected, they go to the component handlers
+    pub(crate) fn submit_component(
+        &self,
+        interaction: MessageComponentInteraction,
+    ) -> Collected<MessageComponentInteraction> {
+        let source = match &interaction.message.interaction {
+            Some(source) => source.id,
+            None => return Collected::Unclaimed(interaction),
+        };
+        let mut components = self.components.lock().unwrap();
+        // Collectors whose command stopped waiting are dropped along the way
+        components.retain(|c| !c.sender.is_closed());
+        let index = components.iter().position(|c| {
+            c.interaction == source && c.user == interaction.user.id && (c.filter)(&interaction)
+        });
+        let sent = match index {
+            Some(index) if components[index].once => {
+                components.remove(index).sender.send(interaction)
+            }
+            Some(index) => components[index].sender.send(interaction),
+            None => return Collected::Unclaimed(interaction),
+        };
+        sent.map_err(|e| e.0).into()
+    }
+
+    /// Checks if a command is waiting for components on the reply to `interaction`
+    pub(crate) fn awaits_reply(&self, interaction: InteractionId) -> bool {
+        self.components
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|c| c.interaction == interaction)
+    }
+}
+
+impl Debug for Collectors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Collectors")
+            .field("modals", &self.modals.lock().unwrap().len())
+            .field("components", &self.components.lock().unwrap().len())
+            .finish()
+    }
+}
+
+/// Collects the components used on the reply to a command, created with
+/// [`Context::collect_components`](crate::Context::collect_components). Interactions are
+/// collected from its creation until it is dropped, so none are missed between two calls to
+/// [`ComponentCollector::next`]
+pub struct ComponentCollector {
+    id: u64,
+    receiver: mpsc::UnboundedReceiver<MessageComponentInteraction>,
+    collectors: Arc<Collectors>,
+    serenity_context: SerenityContext,
+    data: Arc<DataMap>,
+    custom_ids: Option<Arc<CustomIdCodec>>,
+}
+
+impl ComponentCollector {
+    pub(crate) fn new(
+        id: u64,
+        receiver: mpsc::UnboundedReceiver<MessageComponentInteraction>,
+        collectors: Arc<Collectors>,
+        serenity_context: SerenityContext,
+        data: Arc<DataMap>,
+        custom_ids: Option<Arc<CustomIdCodec>>,
+    ) -> Self {
+        Self {
+            id,
+            receiver,
+            collectors,
+            serenity_context,
+            data,
+            custom_ids,
+        }
+    }
+
+    /// Waits for the next collected interaction, returning `None` after the timeout
+    pub async fn next(&mut self, timeout: Duration) -> Option<ComponentContext> {
+        match tokio::time::timeout(timeout, self.receiver.recv()).await {
+            Ok(Some(interaction)) => Some(ComponentContext::from_framework(
+                &self.serenity_context,
+                &interaction,
+                self.data.clone(),
+                self.custom_ids.clone(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl Drop for ComponentCollector {
+    fn drop(&mut self) {
+        self.collectors.cancel_component(self.id);
+    }
+}
+
+impl Debug for ComponentCollector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ComponentCollector")
+    }
+}
@@ -1,5 +1,7 @@
-use crate::numbers::Number;
+use crate::framework::command::time::TimeError;
+use crate::framework::localization::{insert_localizations, LocalizedText};
 use crate::numbers::NumberType;
+use crate::numbers::{clamp_to_discord, display_bound, Number};
 use crate::Context;
 use async_trait::async_trait;
 use core::marker::Sized;
@@ -7,14 +9,18 @@ use core::option::Option;
 use core::option::Option::{None, Some};
 use core::result::Result;
 use core::result::Result::{Err, Ok};
+use regex::Regex;
 use serenity::builder::CreateApplicationCommandOption;
 use serenity::model::channel::ChannelType as SerenityChannelType;
-use serenity::model::channel::GuildChannel;
-use serenity::model::id::{ChannelId, RoleId, UserId};
+use serenity::model::channel::{Attachment, GuildChannel, PartialChannel};
+use serenity::model::guild::{Member, PartialMember, Role};
+use serenity::model::id::{ChannelId, GuildId, RoleId, UserId};
 use serenity::model::interactions::application_command::{
-    ApplicationCommandInteractionDataOptionValue, ApplicationCommandOptionType as SerenityKind,
+    ApplicationCommandInteractionDataOption, ApplicationCommandInteractionDataOptionValue,
+    ApplicationCommandOptionType as SerenityKind,
 };
 use serenity::model::prelude::{Channel, User};
+use std::collections::HashMap;
 use std::fmt::Display;
 
 #[derive(Debug, Copy, Clone)]
@@ -77,22 +83,144 @@ pub fn as_channel_types(channel_types: Vec<ChannelType>) -> Vec<SerenityChannelT
 pub struct CommandArgumentOptions {
     pub min: Option<f64>,
     pub max: Option<f64>,
+    /// Lower bound given as a string (ie `min = "1m"` for a duration)
+    pub min_text: Option<String>,
+    /// Upper bound given as a string (ie `max = "28d"` for a duration)
+    pub max_text: Option<String>,
     pub min_len: Option<u64>,
     pub max_len: Option<u64>,
     pub channel_type: Option<Vec<ChannelType>>,
+    /// Allowed MIME types of an attachment (ie `image/png` or `image/*`)
+    pub content_types: Option<Vec<String>>,
+    /// Maximum size of an attachment in bytes
+    pub max_size: Option<u64>,
+    /// Pattern string arguments must match
+    pub regex: Option<Regex>,
+    /// Choices as `(name, value)` pairs, evaluated once when the command is built.
+    /// Set with `#[option(choices_fn = "...")]`
+    pub choices: Option<Vec<(String, ChoiceValue)>>,
+}
+
+/// Maximum number of choices of an option allowed by Discord
+pub const MAX_CHOICES: usize = 25;
+/// Maximum number of options of a command allowed by Discord
+pub const MAX_OPTIONS: usize = 25;
+
+/// Collects the choices returned by a `choices_fn`, called by the generated command builders
+/// # Panics
+/// Panics if there are more than [`MAX_CHOICES`] choices, since Discord would reject the command
+pub fn collect_choices<N, V>(
+    option: &str,
+    choices: impl IntoIterator<Item = (N, V)>,
+) -> Vec<(String, ChoiceValue)>
+where
+    N: Into<String>,
+    V: Into<ChoiceValue>,
+{
+    let choices: Vec<(String, ChoiceValue)> = choices
+        .into_iter()
+        .map(|(name, value)| (name.into(), value.into()))
+        .collect();
+    assert!(
+        choices.len() <= MAX_CHOICES,
+        "Option `{}` has {} choices, Discord accepts at most {}",
+        option,
+        choices.len(),
+        MAX_CHOICES
+    );
+    choices
+}
+
+/// Value of an option choice
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChoiceValue {
+    String(String),
+    Integer(i64),
+    Number(f64),
+}
+
+impl ChoiceValue {
+    /// Checks if an incoming value is this choice
+    pub fn matches(&self, value: &CommandArgumentValue) -> bool {
+        match (self, value) {
+            (ChoiceValue::String(c), CommandArgumentValue::String(v)) => c == v,
+            (ChoiceValue::Integer(c), CommandArgumentValue::Integer(v)) => c == v,
+            (ChoiceValue::Number(c), CommandArgumentValue::Number(v)) => c == v,
+            (ChoiceValue::Integer(c), CommandArgumentValue::Number(v)) => *c as f64 == *v,
+            _ => false,
+        }
+    }
+
+    fn as_json(&self) -> serde_json::Value {
+        match self {
+            ChoiceValue::String(s) => serde_json::Value::from(s.as_str()),
+            ChoiceValue::Integer(i) => serde_json::Value::from(*i),
+            ChoiceValue::Number(n) => serde_json::Value::from(*n),
+        }
+    }
+}
+
+impl Display for ChoiceValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChoiceValue::String(s) => write!(f, "{}", s),
+            ChoiceValue::Integer(i) => write!(f, "{}", i),
+            ChoiceValue::Number(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl From<String> for ChoiceValue {
+    fn from(value: String) -> Self {
+        ChoiceValue::String(value)
+    }
+}
+
+impl From<&str> for ChoiceValue {
+    fn from(value: &str) -> Self {
+        ChoiceValue::String(value.to_string())
+    }
+}
+
+macro_rules! impl_choice_integer {
+    ($($t:ty),*) => {$(
+        impl From<$t> for ChoiceValue {
+            fn from(value: $t) -> Self {
+                ChoiceValue::Integer(value as i64)
+            }
+        }
+    )*};
+}
+
+impl_choice_integer!(u8, u16, u32, i8, i16, i32, i64);
+
+impl From<f32> for ChoiceValue {
+    fn from(value: f32) -> Self {
+        ChoiceValue::Number(value as f64)
+    }
+}
+
+impl From<f64> for ChoiceValue {
+    fn from(value: f64) -> Self {
+        ChoiceValue::Number(value)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct CommandArgumentSignature {
     pub name: String,
     pub description: String,
+    pub name_localizations: LocalizedText,
+    pub description_localizations: LocalizedText,
+    /// Translations of the names of choices, keyed by the name of the choice
+    pub choice_localizations: HashMap<String, LocalizedText>,
     pub argument: CommandArgumentType,
     pub options: CommandArgumentOptions,
 }
 
 impl CommandArgumentSignature {
     pub fn as_serenity_option(&self) -> CreateApplicationCommandOption {
-        match self.argument.value_type {
+        let mut option = match self.argument.value_type {
             CommandArgumentValueType::String => CreateApplicationCommandOption::default()
                 .name(&self.name)
                 .description(&self.description)
@@ -105,8 +233,8 @@ impl CommandArgumentSignature {
                     .description(&self.description)
                     .kind(self.argument.value_type.as_serenity_kind())
                     .required(!self.argument.optional);
-                o.min_number_value(self.options.min.unwrap_or(min));
-                o.max_number_value(self.options.max.unwrap_or(max));
+                o.min_number_value(clamp_to_discord(self.options.min.unwrap_or(min)));
+                o.max_number_value(clamp_to_discord(self.options.max.unwrap_or(max)));
 
                 o.clone()
             }
@@ -116,8 +244,8 @@ impl CommandArgumentSignature {
                     .description(&self.description)
                     .kind(self.argument.value_type.as_serenity_kind())
                     .required(!self.argument.optional);
-                o.min_number_value(self.options.min.unwrap_or(min));
-                o.max_number_value(self.options.max.unwrap_or(max));
+                o.min_number_value(clamp_to_discord(self.options.min.unwrap_or(min)));
+                o.max_number_value(clamp_to_discord(self.options.max.unwrap_or(max)));
 
                 o.clone()
             }
@@ -151,13 +279,49 @@ impl CommandArgumentSignature {
                 .kind(self.argument.value_type.as_serenity_kind())
                 .required(!self.argument.optional)
                 .clone(),
+            CommandArgumentValueType::Attachment => CreateApplicationCommandOption::default()
+                .name(&self.name)
+                .description(&self.description)
+                .kind(self.argument.value_type.as_serenity_kind())
+                .required(!self.argument.optional)
+                .clone(),
+            CommandArgumentValueType::Mentionable => CreateApplicationCommandOption::default()
+                .name(&self.name)
+                .description(&self.description)
+                .kind(self.argument.value_type.as_serenity_kind())
+                .required(!self.argument.optional)
+                .clone(),
+        };
+
+        // The builder has no localized choices and only takes `i32` integers,
+        // so choices are written like the localizations below
+        if let Some(choices) = &self.options.choices {
+            let choices: Vec<serde_json::Value> = choices
+                .iter()
+                .map(|(name, value)| {
+                    let mut choice = serde_json::json!({ "name": name, "value": value.as_json() });
+                    if let Some(localizations) = self.choice_localizations.get(name) {
+                        choice["name_localizations"] = serde_json::json!(localizations);
+                    }
+                    choice
+                })
+                .collect();
+            option
+                .0
+                .insert("choices", serde_json::Value::Array(choices));
         }
+        insert_localizations(
+            &mut option.0,
+            &self.name_localizations,
+            &self.description_localizations,
+        );
+        option
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct CommandSignature {
-    /// Is context the first argument?
+    /// Does the command take the context as a parameter?
     pub context: bool,
     pub arguments: Vec<CommandArgumentSignature>,
 }
@@ -165,29 +329,130 @@ pub struct CommandSignature {
 #[derive(Debug, Clone)]
 pub struct ArgumentList {
     args: Vec<CommandArgument>,
+    signature: Vec<CommandArgumentSignature>,
     current: usize,
 }
 
 impl ArgumentList {
-    pub fn new(args: Vec<CommandArgument>) -> Self {
-        Self { args, current: 0 }
+    pub fn new(args: Vec<CommandArgument>, signature: Vec<CommandArgumentSignature>) -> Self {
+        Self {
+            args,
+            signature,
+            current: 0,
+        }
+    }
+
+    /// Gets the next argument of the command signature, matched by name with the provided arguments
+    pub async fn arg<A: AsCommandArgument>(&mut self, ctx: &Context) -> Result<A, ArgumentError> {
+        self.arg_with(ctx, |_| Ok(())).await
     }
-    pub async fn arg<A: AsCommandArgument>(&mut self, ctx: &Context) -> A {
+
+    /// Gets the next argument like [`ArgumentList::arg`], running `validator` after conversion
+    pub async fn arg_with<A, F>(&mut self, ctx: &Context, validator: F) -> Result<A, ArgumentError>
+    where
+        A: AsCommandArgument,
+        F: FnOnce(&A) -> Result<(), String>,
+    {
         self.current += 1;
-        match self.args.get(self.current - 1) {
-            Some(v) => v.as_arg::<A>(ctx).await,
-            None => {
-                CommandArgument {
-                    name: "".to_string(),
-                    value: None,
+        let signature = self.signature.get(self.current - 1);
+        let argument = signature
+            .and_then(|s| self.args.iter().find(|a| a.name == s.name))
+            .cloned()
+            .unwrap_or_else(|| CommandArgument {
+                name: signature.map(|s| s.name.clone()).unwrap_or_default(),
+                value: None,
+            });
+        let arg = argument.as_arg::<A>(ctx).await?;
+
+        if let Some(signature) = signature {
+            arg.validate(&signature.options)?;
+
+            if let (Some(choices), Some(value)) = (&signature.options.choices, &argument.value) {
+                if !choices.iter().any(|(_, choice)| choice.matches(value)) {
+                    let names: Vec<&str> = choices.iter().map(|(name, _)| name.as_str()).collect();
+                    return Err(ValidationFailed {
+                        argument: signature.name.clone(),
+                        message: format!("Expected one of: {}", names.join(", ")),
+                    });
+                }
+            }
+
+            if let (Some(regex), Some(CommandArgumentValue::String(value))) =
+                (&signature.options.regex, &argument.value)
+            {
+                if !regex.is_match(value) {
+                    return Err(ValidationFailed {
+                        argument: signature.name.clone(),
+                        message: format!("`{}` does not match the expected format", value),
+                    });
                 }
-                .as_arg::<A>(ctx)
-                .await
             }
         }
+
+        validator(&arg).map_err(|message| ValidationFailed {
+            argument: argument.name.clone(),
+            message,
+        })?;
+        Ok(arg)
+    }
+
+    /// Gets the next `count` arguments as one variadic option (ie `users1` to `users5`),
+    /// collecting the values that were provided. `validator` runs on each value
+    pub async fn variadic<A, F>(
+        &mut self,
+        ctx: &Context,
+        count: usize,
+        min_count: usize,
+        validator: F,
+    ) -> Result<Vec<A>, ArgumentError>
+    where
+        A: AsCommandArgumentValue + Send,
+        F: Fn(&A) -> Result<(), String>,
+    {
+        // Numbered options share the name of the parameter, followed by their position
+        let name = self
+            .signature
+            .get(self.current)
+            .map(|s| {
+                s.name
+                    .trim_end_matches(|c: char| c.is_ascii_digit())
+                    .to_string()
+            })
+            .unwrap_or_default();
+        let mut values = Vec::with_capacity(count);
+        for _ in 0..count {
+            let value = self
+                .arg_with::<Option<A>, _>(ctx, |value| match value {
+                    Some(value) => validator(value),
+                    None => Ok(()),
+                })
+                .await?;
+            values.extend(value);
+        }
+
+        if values.len() < min_count {
+            return Err(ValidationFailed {
+                argument: name,
+                message: format!(
+                    "Expected at least {} values, found {}",
+                    min_count,
+                    values.len()
+                ),
+            });
+        }
+        Ok(values)
     }
 }
 
+/// A group of arguments registered as individual options, taken by a command with `#[flatten]`.
+/// Implemented with `#[derive(CommandArguments)]`
+#[async_trait]
+pub trait CommandArguments: Sized {
+    /// Signatures of the options, in the order they are taken from the argument list
+    fn signatures() -> Vec<CommandArgumentSignature>;
+    async fn from_arguments(ctx: &Context, args: &mut ArgumentList) -> Result<Self, ArgumentError>;
+}
+
 #[derive(Debug, Clone)]
 pub enum CommandArgumentValueType {
     String,
@@ -197,6 +462,8 @@ pub enum CommandArgumentValueType {
     User,
     Role,
     Boolean,
+    Attachment,
+    Mentionable,
 }
 
 impl CommandArgumentValueType {
@@ -209,6 +476,8 @@ impl CommandArgumentValueType {
             CommandArgumentValueType::User => SerenityKind::User,
             CommandArgumentValueType::Role => SerenityKind::Role,
             CommandArgumentValueType::Boolean => SerenityKind::Boolean,
+            CommandArgumentValueType::Attachment => SerenityKind::Attachment,
+            CommandArgumentValueType::Mentionable => SerenityKind::Mentionable,
         }
     }
 }
@@ -218,10 +487,14 @@ pub enum CommandArgumentValue {
     String(String),
     Integer(i64),
     Number(f64),
-    Channel(ChannelId),
-    User(UserId),
-    Role(RoleId),
+    /// Partial channel resolved by Discord
+    Channel(PartialChannel),
+    /// User resolved by Discord, with partial member data if used in a guild
+    User(User, Option<PartialMember>),
+    /// Role resolved by Discord
+    Role(Role),
     Boolean(bool),
+    Attachment(Attachment),
 }
 
 #[derive(Debug, Clone)]
@@ -236,6 +509,90 @@ pub enum ArgumentError {
     ArgumentPreprocessingError(String),
     /// Error parsing a nested type (ie if an Option<User> failed parsing User)
     NestedParsingError(Box<Self>),
+    /// Error extracting a parameter from the interaction (ie a guild that is not cached)
+    ExtractionError(String),
+    /// Argument or extracted parameter requires a guild, but the command was used in a direct message
+    NotInGuild,
+    /// Attachment does not have an allowed content type or is too large
+    InvalidAttachment(String),
+    /// Argument was rejected by a validator (ie `#[option(validate = "...")]` or `#[option(regex = "...")]`)
+    ValidationFailed { argument: String, message: String },
+    /// Duration or date could not be parsed, or is out of the option's range
+    InvalidTime(TimeError),
+    /// String is not a valid ID (ie for a `MessageId` or `EmojiId` argument)
+    InvalidId(String),
+    /// Message link is malformed or points outside of the current guild
+    InvalidMessageLink(String),
+    /// The bot or the user is not allowed to access the requested resource
+    MissingPermissions(String),
+    /// String could not be parsed into the argument type (ie an invalid colour or link)
+    InvalidValue(String),
+    /// Zero was given for a non-zero number (ie a `NonZeroI32` argument)
+    Zero,
+}
+
+impl Display for ArgumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnknownIncomingType => write!(f, "Received an unknown argument type"),
+            IncorrectIncomingType(msg)
+            | IncomingArgumentNotProvided(msg)
+            | ArgumentPreprocessingError(msg)
+            | ExtractionError(msg)
+            | InvalidAttachment(msg)
+            | InvalidMessageLink(msg)
+            | MissingPermissions(msg)
+            | InvalidValue(msg) => write!(f, "{}", msg),
+            InvalidId(id) => write!(f, "`{}` is not a valid ID", id),
+            NestedParsingError(e) => write!(f, "{}", e),
+            NotInGuild => write!(f, "This command can only be used in a guild"),
+            Zero => write!(f, "The value must not be zero"),
+            InvalidTime(e) => write!(f, "{}", e),
+            ValidationFailed { argument, message } => {
+                write!(f, "Invalid value for `{}`: {}", argument, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgumentError {}
+
+impl ArgumentError {
+    /// Fluent message id used to translate this error with the `i18n` feature
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            UnknownIncomingType => "ataraxy-unknown-type",
+            IncorrectIncomingType(_) => "ataraxy-incorrect-type",
+            IncomingArgumentNotProvided(_) => "ataraxy-not-provided",
+            ArgumentPreprocessingError(_) => "ataraxy-preprocessing",
+            NestedParsingError(e) => e.message_id(),
+            ExtractionError(_) => "ataraxy-extraction",
+            NotInGuild => "ataraxy-not-in-guild",
+            InvalidAttachment(_) => "ataraxy-invalid-attachment",
+            ValidationFailed { .. } => "ataraxy-validation-failed",
+            InvalidTime(e) => e.message_id(),
+            InvalidId(_) => "ataraxy-invalid-id",
+            InvalidMessageLink(_) => "ataraxy-invalid-message-link",
+            MissingPermissions(_) => "ataraxy-missing-permissions",
+            InvalidValue(_) => "ataraxy-invalid-value",
+            Zero => "ataraxy-zero",
+        }
+    }
+
+    /// Arguments of the Fluent message of this error besides `message`, which holds the English
+    /// text: the `argument` and `reason` of failed validations, the rejected `value` of IDs,
+    /// and the arguments of [`TimeError::message_args`] for durations and dates
+    pub fn message_args(&self) -> Vec<(&'static str, String)> {
+        match self {
+            NestedParsingError(e) => e.message_args(),
+            ValidationFailed { argument, message } => {
+                vec![("argument", argument.clone()), ("reason", message.clone())]
+            }
+            InvalidTime(e) => e.message_args(),
+            InvalidId(id) => vec![("value", id.clone())],
+            _ => vec![],
+        }
+    }
 }
 
 use ArgumentError::*;
@@ -248,13 +605,33 @@ impl CommandArgumentValue {
             ApplicationCommandInteractionDataOptionValue::String(s) => Self::String(s.clone()),
             ApplicationCommandInteractionDataOptionValue::Integer(i) => Self::Integer(*i),
             ApplicationCommandInteractionDataOptionValue::Boolean(b) => Self::Boolean(*b),
-            ApplicationCommandInteractionDataOptionValue::User(u, _) => Self::User(u.id),
-            ApplicationCommandInteractionDataOptionValue::Channel(c) => Self::Channel(c.id),
-            ApplicationCommandInteractionDataOptionValue::Role(r) => Self::Role(r.id),
+            ApplicationCommandInteractionDataOptionValue::User(u, m) => {
+                Self::User(u.clone(), m.clone())
+            }
+            ApplicationCommandInteractionDataOptionValue::Channel(c) => Self::Channel(c.clone()),
+            ApplicationCommandInteractionDataOptionValue::Role(r) => Self::Role(r.clone()),
             ApplicationCommandInteractionDataOptionValue::Number(n) => Self::Number(*n),
+            ApplicationCommandInteractionDataOptionValue::Attachment(a) => {
+                Self::Attachment(a.clone())
+            }
             _ => return Err(ArgumentError::UnknownIncomingType),
         })
     }
+
+    /// Builds a value for an option Discord sent without resolved data, looking up roles in the guild cache
+    pub async fn from_unresolved(
+        ctx: &Context,
+        option: &ApplicationCommandInteractionDataOption,
+    ) -> Option<Self> {
+        match option.kind {
+            SerenityKind::Role | SerenityKind::Mentionable => {
+                let role_id = RoleId(option.value.as_ref()?.as_str()?.parse().ok()?);
+                let role = ctx.cache().role(ctx.guild_id()?, role_id).await?;
+                Some(Self::Role(role))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl Display for CommandArgumentValue {
@@ -267,9 +644,10 @@ impl Display for CommandArgumentValue {
                 Self::Integer(_) => "Integer",
                 Self::Number(_) => "Float",
                 CommandArgumentValue::Channel(_) => "Channel",
-                CommandArgumentValue::User(_) => "User",
+                CommandArgumentValue::User(_, _) => "User",
                 CommandArgumentValue::Role(_) => "Role",
                 CommandArgumentValue::Boolean(_) => "Boolean",
+                CommandArgumentValue::Attachment(_) => "Attachment",
             }
         )
     }
@@ -288,15 +666,13 @@ pub struct CommandArgument {
 }
 
 impl CommandArgument {
-    pub async fn as_arg<T: AsCommandArgument>(&self, ctx: &Context) -> T {
-        T::from_returned_argument(ctx, self.value.clone())
-            .await
-            .expect("Error parsing argument")
+    pub async fn as_arg<T: AsCommandArgument>(&self, ctx: &Context) -> Result<T, ArgumentError> {
+        T::from_returned_argument(ctx, self.value.clone()).await
     }
 }
 
 /// Represents a value that can be passed to a command, like a string, integer, or file
-/// Note: This is not made for wrappers like Vec<T> or Option<T> as they should use AsCommandArgument
+/// Note: This is not made for wrappers like Option<T> as they should use AsCommandArgument
 #[async_trait]
 pub trait AsCommandArgumentValue {
     fn value_type() -> CommandArgumentValueType;
@@ -306,10 +682,16 @@ pub trait AsCommandArgumentValue {
     ) -> Result<Self, ArgumentError>
     where
         Self: Sized;
+
+    /// Validates a converted value against the options of its argument
+    fn validate(&self, _options: &CommandArgumentOptions) -> Result<(), ArgumentError> {
+        Ok(())
+    }
 }
 
-/// Trait for wrappers such as Vec<T> or Option<T>
-/// Auto implemented for items that impl AsCommandArgumentValue
+/// Trait for wrappers such as Option<T>
+/// Auto implemented for items that impl AsCommandArgumentValue.
+/// `Vec<T>` is taken as numbered options instead, see [`ArgumentList::variadic`]
 #[async_trait]
 pub trait AsCommandArgument {
     fn command_argument_type() -> CommandArgumentType;
@@ -319,6 +701,9 @@ pub trait AsCommandArgument {
     ) -> Result<Self, ArgumentError>
     where
         Self: Sized;
+
+    /// Validates a converted value against the options of its argument
+    fn validate(&self, options: &CommandArgumentOptions) -> Result<(), ArgumentError>;
 }
 
 #[async_trait]
@@ -339,6 +724,10 @@ where
     ) -> Result<Self, ArgumentError> {
         T::from_returned_argument(ctx, arg).await
     }
+
+    fn validate(&self, options: &CommandArgumentOptions) -> Result<(), ArgumentError> {
+        AsCommandArgumentValue::validate(self, options)
+    }
 }
 
 #[async_trait]
@@ -365,6 +754,13 @@ where
         }
         Ok(None)
     }
+
+    fn validate(&self, options: &CommandArgumentOptions) -> Result<(), ArgumentError> {
+        match self {
+            Some(value) => value.validate(options),
+            None => Ok(()),
+        }
+    }
 }
 
 #[async_trait]
@@ -392,6 +788,20 @@ impl AsCommandArgumentValue for String {
     }
 }
 
+/// Takes the value of a string option, for types parsed from text
+pub(crate) fn expect_string(arg: Option<CommandArgumentValue>) -> Result<String, ArgumentError> {
+    match arg {
+        Some(CommandArgumentValue::String(s)) => Ok(s),
+        Some(arg) => Err(IncorrectIncomingType(format!(
+            "Expected string, found: {}",
+            arg
+        ))),
+        None => Err(IncomingArgumentNotProvided(
+            "Required argument not provided".to_string(),
+        )),
+    }
+}
+
 #[async_trait]
 impl<T: Number> AsCommandArgumentValue for T {
     fn value_type() -> CommandArgumentValueType {
@@ -405,34 +815,90 @@ impl<T: Number> AsCommandArgumentValue for T {
         _ctx: &Context,
         arg: Option<CommandArgumentValue>,
     ) -> Result<Self, ArgumentError> {
-        if let Some(arg) = arg {
-            match T::number_type() {
-                NumberType::Integer => {
-                    if let CommandArgumentValue::Integer(arg) = arg {
-                        return Ok(T::from_f64(arg as f64));
-                    }
-                    return Err(IncorrectIncomingType(format!(
-                        "Expected integer, found: {}",
-                        arg
-                    )));
-                }
-                NumberType::Float => {
-                    if let CommandArgumentValue::Number(arg) = arg {
-                        return Ok(T::from_f64(arg));
+        number_argument(arg)
+    }
+}
+
+/// Converts an integer or number option into a [`Number`]
+fn number_argument<T: Number>(arg: Option<CommandArgumentValue>) -> Result<T, ArgumentError> {
+    if let Some(arg) = arg {
+        // Discord's range of signed non-zero types includes zero
+        let zero = match arg {
+            CommandArgumentValue::Integer(i) => i == 0,
+            CommandArgumentValue::Number(n) => n == 0.0,
+            _ => false,
+        };
+        if T::NON_ZERO && zero {
+            return Err(Zero);
+        }
+        let out_of_range = |value: &dyn Display| {
+            ArgumentPreprocessingError(format!(
+                "{} is out of range, expected a value between {} and {}",
+                value,
+                display_bound::<T>(T::MIN),
+                display_bound::<T>(T::MAX)
+            ))
+        };
+        return match (T::number_type(), arg) {
+            (_, CommandArgumentValue::Integer(i)) => T::from_i64(i).ok_or_else(|| out_of_range(&i)),
+            (NumberType::Integer, CommandArgumentValue::Number(n)) if n.fract() != 0.0 => Err(
+                ArgumentPreprocessingError(format!("Expected a whole number, found: {}", n)),
+            ),
+            (_, CommandArgumentValue::Number(n)) => T::from_f64(n).ok_or_else(|| out_of_range(&n)),
+            (NumberType::Integer, arg) => Err(IncorrectIncomingType(format!(
+                "Expected integer, found: {}",
+                arg
+            ))),
+            (NumberType::Float, arg) => Err(IncorrectIncomingType(format!(
+                "Expected number, found: {}",
+                arg
+            ))),
+        };
+    }
+    Err(IncomingArgumentNotProvided(
+        "Required argument not provided".to_string(),
+    ))
+}
+
+/// Implements integers too large for Discord's integer options, passed as string options
+macro_rules! impl_string_integer {
+    ($($t:ty),*) => {$(
+        #[async_trait]
+        impl AsCommandArgumentValue for $t {
+            fn value_type() -> CommandArgumentValueType {
+                CommandArgumentValueType::String
+            }
+
+            async fn from_returned_argument(
+                _ctx: &Context,
+                arg: Option<CommandArgumentValue>,
+            ) -> Result<Self, ArgumentError> {
+                if let Some(arg) = arg {
+                    if let CommandArgumentValue::String(arg) = arg {
+                        return arg.trim().parse::<$t>().map_err(|_e| {
+                            ArgumentPreprocessingError(format!(
+                                "`{}` is not a valid integer between {} and {}",
+                                arg,
+                                <$t>::MIN,
+                                <$t>::MAX
+                            ))
+                        });
                     }
                     return Err(IncorrectIncomingType(format!(
-                        "Expected number, found: {}",
+                        "Expected string, found: {}",
                         arg
                     )));
                 }
+                Err(IncomingArgumentNotProvided(
+                    "Required argument not provided".to_string(),
+                ))
             }
         }
-        Err(IncomingArgumentNotProvided(
-            "Required argument not provided".to_string(),
-        ))
-    }
+    )*};
 }
 
+impl_string_integer!(u128, i128);
+
 #[async_trait]
 impl AsCommandArgumentValue for bool {
     fn value_type() -> CommandArgumentValueType {
@@ -475,8 +941,8 @@ impl AsCommandArgumentValue for UserId {
         Self: Sized,
     {
         if let Some(arg) = arg {
-            if let CommandArgumentValue::User(arg) = arg {
-                return Ok(arg);
+            if let CommandArgumentValue::User(user, _) = arg {
+                return Ok(user.id);
             }
             return Err(IncorrectIncomingType(format!(
                 "Expected user, found: {}",
@@ -496,17 +962,15 @@ impl AsCommandArgumentValue for User {
     }
 
     async fn from_returned_argument(
-        ctx: &Context,
+        _ctx: &Context,
         arg: Option<CommandArgumentValue>,
     ) -> Result<Self, ArgumentError>
     where
         Self: Sized,
     {
         if let Some(arg) = arg {
-            if let CommandArgumentValue::User(arg) = arg {
-                return arg.to_user(&ctx.http()).await.map_err(|_e| {
-                    ArgumentPreprocessingError("Error fetching cached user".to_string())
-                });
+            if let CommandArgumentValue::User(user, _) = arg {
+                return Ok(user);
             }
             return Err(IncorrectIncomingType(format!(
                 "Expected user, found: {}",
@@ -533,8 +997,36 @@ impl AsCommandArgumentValue for RoleId {
         Self: Sized,
     {
         if let Some(arg) = arg {
-            if let CommandArgumentValue::Role(arg) = arg {
-                return Ok(arg);
+            if let CommandArgumentValue::Role(role) = arg {
+                return Ok(role.id);
+            }
+            return Err(IncorrectIncomingType(format!(
+                "Expected role, found: {}",
+                arg
+            )));
+        }
+        Err(IncomingArgumentNotProvided(
+            "Required argument not provided".to_string(),
+        ))
+    }
+}
+
+#[async_trait]
+impl AsCommandArgumentValue for Role {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::Role
+    }
+
+    async fn from_returned_argument(
+        _ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError>
+    where
+        Self: Sized,
+    {
+        if let Some(arg) = arg {
+            if let CommandArgumentValue::Role(role) = arg {
+                return Ok(role);
             }
             return Err(IncorrectIncomingType(format!(
                 "Expected role, found: {}",
@@ -561,8 +1053,8 @@ impl AsCommandArgumentValue for ChannelId {
         Self: Sized,
     {
         if let Some(arg) = arg {
-            if let CommandArgumentValue::Channel(arg) = arg {
-                return Ok(arg);
+            if let CommandArgumentValue::Channel(channel) = arg {
+                return Ok(channel.id);
             }
             return Err(IncorrectIncomingType(format!(
                 "Expected channel, found: {}",
@@ -589,9 +1081,9 @@ impl AsCommandArgumentValue for Channel {
         Self: Sized,
     {
         if let Some(arg) = arg {
-            if let CommandArgumentValue::Channel(arg) = arg {
-                return arg.to_channel(&ctx.http()).await.map_err(|_e| {
-                    ArgumentPreprocessingError("Error fetching cached user".to_string())
+            if let CommandArgumentValue::Channel(channel) = arg {
+                return channel.id.to_channel(ctx).await.map_err(|_e| {
+                    ArgumentPreprocessingError("Error fetching channel".to_string())
                 });
             }
             return Err(IncorrectIncomingType(format!(
@@ -619,13 +1111,12 @@ impl AsCommandArgumentValue for GuildChannel {
         Self: Sized,
     {
         if let Some(arg) = arg {
-            if let CommandArgumentValue::Channel(arg) = arg {
-                return arg
-                    .to_channel(&ctx.http())
+            if let CommandArgumentValue::Channel(channel) = arg {
+                return channel
+                    .id
+                    .to_channel(ctx)
                     .await
-                    .map_err(|_e| {
-                        ArgumentPreprocessingError("Error fetching cached channel".to_string())
-                    })
+                    .map_err(|_e| ArgumentPreprocessingError("Error fetching channel".to_string()))
                     .and_then(|c| match c {
                         Channel::Category(_) => Err(ArgumentPreprocessingError(
                             "Expected Guild Channel, found Channel Category".to_string(),
@@ -649,3 +1140,287 @@ impl AsCommandArgumentValue for GuildChannel {
         ))
     }
 }
+
+#[async_trait]
+impl AsCommandArgumentValue for PartialMember {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::User
+    }
+
+    async fn from_returned_argument(
+        ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError>
+    where
+        Self: Sized,
+    {
+        if let Some(arg) = arg {
+            if let CommandArgumentValue::User(user, member) = arg {
+                let guild_id = ctx.guild_id().ok_or(NotInGuild)?;
+                let mut member = member.ok_or_else(|| {
+                    ArgumentPreprocessingError(format!(
+                        "User {} is not a member of this guild",
+                        user.tag()
+                    ))
+                })?;
+                member.guild_id.get_or_insert(guild_id);
+                member.user.get_or_insert(user);
+                return Ok(member);
+            }
+            return Err(IncorrectIncomingType(format!(
+                "Expected user, found: {}",
+                arg
+            )));
+        }
+        Err(IncomingArgumentNotProvided(
+            "Required argument not provided".to_string(),
+        ))
+    }
+}
+
+#[async_trait]
+impl AsCommandArgumentValue for Member {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::User
+    }
+
+    async fn from_returned_argument(
+        ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError>
+    where
+        Self: Sized,
+    {
+        let member =
+            <PartialMember as AsCommandArgumentValue>::from_returned_argument(ctx, arg).await?;
+        match (member.guild_id, member.user) {
+            (Some(guild_id), Some(user)) => into_member(ctx, guild_id, &user).await,
+            _ => Err(NotInGuild),
+        }
+    }
+}
+
+/// Gets the full member of a resolved user. `Member` cannot be built from the resolved partial
+/// member data, so it is read from the cache, or fetched if the guild members are not cached
+async fn into_member(
+    ctx: &Context,
+    guild_id: GuildId,
+    user: &User,
+) -> Result<Member, ArgumentError> {
+    guild_id.member(ctx, user.id).await.map_err(|_e| {
+        ArgumentPreprocessingError(format!("User {} is not a member of this guild", user.tag()))
+    })
+}
+
+/// A user, guild member or role passed to a mentionable option
+#[derive(Debug, Clone)]
+pub enum Mentionable {
+    /// A user mentioned outside of a guild, or who is not a member of the guild
+    User(User),
+    /// A member of the guild the command was used in, with the member data resolved by Discord
+    Member(User, PartialMember),
+    Role(Role),
+}
+
+impl Mentionable {
+    /// Gets the user of a mentioned user or member
+    pub fn user(&self) -> Option<&User> {
+        match self {
+            Mentionable::User(user) => Some(user),
+            Mentionable::Member(user, _) => Some(user),
+            Mentionable::Role(_) => None,
+        }
+    }
+
+    pub fn role(&self) -> Option<&Role> {
+        match self {
+            Mentionable::Role(role) => Some(role),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Mentionable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mentionable::User(user) => write!(f, "<@{}>", user.id),
+            Mentionable::Member(user, _) => write!(f, "<@{}>", user.id),
+            Mentionable::Role(role) => write!(f, "<@&{}>", role.id),
+        }
+    }
+}
+
+#[async_trait]
+impl AsCommandArgumentValue for Mentionable {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::Mentionable
+    }
+
+    async fn from_returned_argument(
+        _ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError>
+    where
+        Self: Sized,
+    {
+        if let Some(arg) = arg {
+            return match arg {
+                // The resolved member is used as is, without fetching the full member
+                CommandArgumentValue::User(user, Some(member)) => {
+                    Ok(Mentionable::Member(user, member))
+                }
+                CommandArgumentValue::User(user, None) => Ok(Mentionable::User(user)),
+                CommandArgumentValue::Role(role) => Ok(Mentionable::Role(role)),
+                arg => Err(IncorrectIncomingType(format!(
+                    "Expected user or role, found: {}",
+                    arg
+                ))),
+            };
+        }
+        Err(IncomingArgumentNotProvided(
+            "Required argument not provided".to_string(),
+        ))
+    }
+}
+
+/// Checks a MIME type against an allowed type, which may use a wildcard subtype (ie `image/*`)
+fn content_type_matches(allowed: &str, content_type: &str) -> bool {
+    let allowed = allowed.trim();
+    match allowed.strip_suffix("/*") {
+        Some(kind) => content_type
+            .split('/')
+            .next()
+            .map(|k| k.eq_ignore_ascii_case(kind))
+            .unwrap_or(false),
+        None => allowed.eq_ignore_ascii_case(content_type),
+    }
+}
+
+#[async_trait]
+impl AsCommandArgumentValue for Attachment {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::Attachment
+    }
+
+    async fn from_returned_argument(
+        _ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError>
+    where
+        Self: Sized,
+    {
+        if let Some(arg) = arg {
+            if let CommandArgumentValue::Attachment(attachment) = arg {
+                return Ok(attachment);
+            }
+            return Err(IncorrectIncomingType(format!(
+                "Expected attachment, found: {}",
+                arg
+            )));
+        }
+        Err(IncomingArgumentNotProvided(
+            "Required argument not provided".to_string(),
+        ))
+    }
+
+    fn validate(&self, options: &CommandArgumentOptions) -> Result<(), ArgumentError> {
+        if let Some(max_size) = options.max_size {
+            if self.size > max_size {
+                return Err(InvalidAttachment(format!(
+                    "{} is larger than the maximum size of {} bytes",
+                    self.filename, max_size
+                )));
+            }
+        }
+        if let Some(content_types) = &options.content_types {
+            let content_type = self
+                .content_type
+                .as_deref()
+                .and_then(|c| c.split(';').next())
+                .unwrap_or("")
+                .trim();
+            if !content_types
+                .iter()
+                .any(|allowed| content_type_matches(allowed, content_type))
+            {
+                return Err(InvalidAttachment(format!(
+                    "{} must be one of: {}",
+                    self.filename,
+                    content_types.join(", ")
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_choices() {
+        let choices = collect_choices("size", vec![("Small", 1), ("Large", 2)]);
+        assert_eq!(
+            choices,
+            vec![
+                ("Small".to_string(), ChoiceValue::Integer(1)),
+                ("Large".to_string(), ChoiceValue::Integer(2)),
+            ]
+        );
+        assert!(choices[1].1.matches(&CommandArgumentValue::Integer(2)));
+        assert!(!choices[1].1.matches(&CommandArgumentValue::Integer(3)));
+        assert_eq!(
+            collect_choices("size", (0..25).map(|i| (i.to_string(), i))).len(),
+            25
+        );
+    }
+
+    #[test]
+    fn rejects_zero_for_non_zero_numbers() {
+        use std::num::{NonZeroI32, NonZeroU8};
+        assert!(matches!(
+            number_argument::<NonZeroI32>(Some(CommandArgumentValue::Integer(0))),
+            Err(Zero)
+        ));
+        assert!(matches!(
+            number_argument::<NonZeroU8>(Some(CommandArgumentValue::Number(0.0))),
+            Err(Zero)
+        ));
+        assert_eq!(
+            number_argument::<NonZeroI32>(Some(CommandArgumentValue::Integer(-3))).unwrap(),
+            NonZeroI32::new(-3).unwrap()
+        );
+        assert_eq!(
+            number_argument::<i32>(Some(CommandArgumentValue::Integer(0))).unwrap(),
+            0
+        );
+        assert_eq!(Zero.message_id(), "ataraxy-zero");
+    }
+
+    #[test]
+    fn reports_exact_bounds() {
+        let message = |error: ArgumentError| match error {
+            ArgumentPreprocessingError(message) => message,
+            other => panic!("Expected an out of range error, got {:?}", other),
+        };
+        assert_eq!(
+            message(number_argument::<u64>(Some(CommandArgumentValue::Integer(-1))).unwrap_err()),
+            "-1 is out of range, expected a value between 0 and 9007199254740992"
+        );
+        assert_eq!(
+            message(number_argument::<i8>(Some(CommandArgumentValue::Integer(200))).unwrap_err()),
+            "200 is out of range, expected a value between -128 and 127"
+        );
+        assert_eq!(
+            message(number_argument::<f32>(Some(CommandArgumentValue::Number(1e39))).unwrap_err()),
+            "1000000000000000000000000000000000000000 is out of range, expected a value between -9007199254740992 and 9007199254740992"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Option `size` has 26 choices, Discord accepts at most 25")]
+    fn rejects_too_many_choices() {
+        collect_choices("size", (0..26).map(|i| (i.to_string(), i)));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::framework::command::argument::ArgumentError;
+use crate::Context;
+use async_trait::async_trait;
+use serenity::model::guild::{Guild, Member};
+use serenity::model::user::User;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Represents a command parameter that is filled by the framework from the interaction
+/// instead of being registered as a Discord option.
+/// Parameters are marked as extractors with `#[extract]` in the [`command`](crate::command) macro
+#[async_trait]
+pub trait FromContext {
+    async fn from_context(ctx: &Context) -> Result<Self, ArgumentError>
+    where
+        Self: Sized;
+}
+
+#[async_trait]
+impl<T: FromContext + Send> FromContext for Option<T> {
+    async fn from_context(ctx: &Context) -> Result<Self, ArgumentError> {
+        Ok(T::from_context(ctx).await.ok())
+    }
+}
+
+/// Shared data registered with [`Framework::data`](crate::Framework::data)
+#[derive(Debug)]
+pub struct Data<T>(pub Arc<T>);
+
+impl<T> Clone for Data<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Deref for Data<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl<T: Send + Sync + 'static> FromContext for Data<T> {
+    async fn from_context(ctx: &Context) -> Result<Self, ArgumentError> {
+        ctx.try_data::<T>().map(Data).ok_or_else(|| {
+            ArgumentError::ExtractionError(format!(
+                "No data of type {} registered in the framework",
+                std::any::type_name::<T>()
+            ))
+        })
+    }
+}
+
+/// The user who invoked the command
+#[derive(Debug, Clone)]
+pub struct Author(pub User);
+
+impl Deref for Author {
+    type Target = User;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl FromContext for Author {
+    async fn from_context(ctx: &Context) -> Result<Self, ArgumentError> {
+        Ok(Author(ctx.user().clone()))
+    }
+}
+
+/// The locale selected by the user who invoked the command
+#[derive(Debug, Clone)]
+pub struct Locale(pub String);
+
+impl Deref for Locale {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl FromContext for Locale {
+    async fn from_context(ctx: &Context) -> Result<Self, ArgumentError> {
+        Ok(Locale(ctx.locale().to_string()))
+    }
+}
+
+/// The guild member who invoked the command
+#[async_trait]
+impl FromContext for Member {
+    async fn from_context(ctx: &Context) -> Result<Self, ArgumentError> {
+        ctx.member().cloned().ok_or(ArgumentError::NotInGuild)
+    }
+}
+
+/// The guild the command was invoked in, read from the cache
+#[async_trait]
+impl FromContext for Guild {
+    async fn from_context(ctx: &Context) -> Result<Self, ArgumentError> {
+        let guild_id = ctx.guild_id().ok_or(ArgumentError::NotInGuild)?;
+        guild_id
+            .to_guild_cached(ctx.cache())
+            .await
+            .ok_or_else(|| ArgumentError::ExtractionError("Guild is not in the cache".to_string()))
+    }
+}
@@ -1,6 +1,11 @@
 pub mod argument;
+pub mod extract;
+pub mod snowflake;
+pub mod text;
+pub mod time;
 
-use crate::framework::command::argument::{ArgumentList, CommandSignature};
+use crate::framework::command::argument::{ArgumentError, ArgumentList, CommandSignature};
+use crate::framework::localization::LocalizedText;
 
 use std::fmt::{Debug, Formatter};
 use std::future::Future;
@@ -12,7 +17,7 @@ pub struct CommandError {}
 type Handler = for<'a> fn(
     super::context::Context,
     &'a ArgumentList,
-) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+) -> Pin<Box<dyn Future<Output = Result<(), ArgumentError>> + Send>>;
 
 #[derive(Clone)]
 pub struct CommandHandler(pub Handler);
@@ -27,6 +32,8 @@ impl Debug for CommandHandler {
 pub struct Command {
     pub name: String,
     pub description: String,
+    pub name_localizations: LocalizedText,
+    pub description_localizations: LocalizedText,
     pub guilds: Option<Vec<u64>>,
     pub arguments: CommandSignature,
     pub action: CommandHandler,
@@ -0,0 +1,376 @@
+//! IDs and messages parsed from string options, since Discord has no message option type
+//!
+//! [`MessageId`] and [`Message`] accept a message ID, a `channel_id-message_id` pair (as copied with
+//! shift held in the Discord client) or a message link such as
+//! `https://discord.com/channels/81384788765712384/381880193251409931/381897048103346186`.
+//! A bare ID is looked up in the channel the command was used in.
+//!
+//! [`GuildId`] accepts an ID or a link to a channel or message of the guild,
+//! and [`EmojiId`] accepts an ID or a custom emoji such as `<:ferris:381880193251409931>`.
+
+use crate::framework::command::argument::{
+    expect_string, ArgumentError, AsCommandArgumentValue, CommandArgumentValue,
+    CommandArgumentValueType,
+};
+use crate::Context;
+use async_trait::async_trait;
+use serenity::http::error::Error as HttpError;
+use serenity::model::channel::{Channel, Message};
+use serenity::model::id::{ChannelId, EmojiId, GuildId, MessageId};
+use serenity::model::Permissions;
+
+/// Parses a raw snowflake ID
+fn parse_id(input: &str) -> Result<u64, ArgumentError> {
+    input
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|id| *id != 0)
+        .ok_or_else(|| ArgumentError::InvalidId(input.trim().to_string()))
+}
+
+/// Reference to a message parsed from an ID, a `channel-message` pair or a message link
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageLink {
+    /// Guild of the message, `None` for direct messages or when only IDs were given
+    pub guild_id: Option<GuildId>,
+    /// Channel of the message, `None` when only the message ID was given
+    pub channel_id: Option<ChannelId>,
+    pub message_id: MessageId,
+}
+
+impl MessageLink {
+    pub fn parse(input: &str) -> Result<Self, ArgumentError> {
+        let input = input.trim().trim_start_matches('<').trim_end_matches('>');
+
+        if let Some(path) = link_path(input) {
+            let malformed = || {
+                ArgumentError::InvalidMessageLink(format!(
+                    "`{}` is not a valid message link, expected `https://discord.com/channels/<server>/<channel>/<message>`",
+                    input
+                ))
+            };
+            let parts: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+            if parts.len() != 3 {
+                return Err(malformed());
+            }
+            let guild_id = match parts[0] {
+                "@me" => None,
+                id => Some(GuildId(parse_id(id).map_err(|_| malformed())?)),
+            };
+            return Ok(Self {
+                guild_id,
+                channel_id: Some(ChannelId(parse_id(parts[1]).map_err(|_| malformed())?)),
+                message_id: MessageId(parse_id(parts[2]).map_err(|_| malformed())?),
+            });
+        }
+
+        if let Some((channel, message)) = input.split_once('-') {
+            return Ok(Self {
+                guild_id: None,
+                channel_id: Some(ChannelId(parse_id(channel)?)),
+                message_id: MessageId(parse_id(message)?),
+            });
+        }
+
+        Ok(Self {
+            guild_id: None,
+            channel_id: None,
+            message_id: MessageId(parse_id(input)?),
+        })
+    }
+}
+
+/// Returns the part of a Discord link after `/channels/`, if the input is a Discord link
+fn link_path(input: &str) -> Option<&str> {
+    let rest = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))
+        .unwrap_or(input);
+    let rest = rest
+        .strip_prefix("ptb.")
+        .or_else(|| rest.strip_prefix("canary."))
+        .unwrap_or(rest);
+    let rest = rest
+        .strip_prefix("discord.com/")
+        .or_else(|| rest.strip_prefix("discordapp.com/"))?;
+    Some(rest.strip_prefix("channels/").unwrap_or(rest))
+}
+
+#[async_trait]
+impl AsCommandArgumentValue for MessageId {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::String
+    }
+
+    async fn from_returned_argument(
+        _ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError> {
+        MessageLink::parse(&expect_string(arg)?).map(|link| link.message_id)
+    }
+}
+
+#[async_trait]
+impl AsCommandArgumentValue for GuildId {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::String
+    }
+
+    async fn from_returned_argument(
+        _ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError> {
+        let input = expect_string(arg)?;
+        match link_path(input.trim()) {
+            Some(path) => path
+                .split('/')
+                .next()
+                .and_then(|id| parse_id(id).ok())
+                .map(GuildId)
+                .ok_or_else(|| {
+                    ArgumentError::InvalidMessageLink(format!(
+                        "`{}` is not a link to a server",
+                        input.trim()
+                    ))
+                }),
+            None => parse_id(&input).map(GuildId),
+        }
+    }
+}
+
+#[async_trait]
+impl AsCommandArgumentValue for EmojiId {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::String
+    }
+
+    async fn from_returned_argument(
+        _ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError> {
+        let input = expect_string(arg)?;
+        let trimmed = input.trim();
+        // Custom emojis are formatted as `<:name:id>` or `<a:name:id>` when animated
+        let id = match trimmed.strip_prefix('<').and_then(|e| e.strip_suffix('>')) {
+            Some(emoji) => emoji
+                .rsplit(':')
+                .next()
+                .ok_or_else(|| ArgumentError::InvalidId(trimmed.to_string()))?,
+            None => trimmed,
+        };
+        parse_id(id).map(EmojiId)
+    }
+}
+
+/// Message fetched from the cache or the API.
+/// Links must point into the current guild (or the current channel in direct messages),
+/// and the invoking user must be able to read the linked channel
+#[async_trait]
+impl AsCommandArgumentValue for Message {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::String
+    }
+
+    async fn from_returned_argument(
+        ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError> {
+        let link = MessageLink::parse(&expect_string(arg)?)?;
+        let channel_id = link.channel_id.unwrap_or_else(|| ctx.channel_id());
+
+        match (link.guild_id, ctx.guild_id()) {
+            (Some(linked), Some(current)) if linked != current => {
+                return Err(ArgumentError::InvalidMessageLink(
+                    "Message link points to another server".to_string(),
+                ))
+            }
+            (Some(_), None) => {
+                return Err(ArgumentError::InvalidMessageLink(
+                    "Message link points to a server, but the command was used in a direct message"
+                        .to_string(),
+                ))
+            }
+            _ => (),
+        }
+
+        check_readable(ctx, channel_id).await?;
+
+        if let Some(message) = ctx.cache().message(channel_id, link.message_id).await {
+            return Ok(message);
+        }
+        ctx.http()
+            .get_message(channel_id.0, link.message_id.0)
+            .await
+            .map_err(|e| match e {
+                serenity::Error::Http(e) => match *e {
+                    HttpError::UnsuccessfulRequest(response)
+                        if response.status_code.as_u16() == 403 =>
+                    {
+                        ArgumentError::MissingPermissions(format!(
+                            "I am not allowed to read messages in <#{}>",
+                            channel_id
+                        ))
+                    }
+                    HttpError::UnsuccessfulRequest(response)
+                        if response.status_code.as_u16() == 404 =>
+                    {
+                        ArgumentError::ArgumentPreprocessingError(format!(
+                            "Message {} was not found in <#{}>",
+                            link.message_id, channel_id
+                        ))
+                    }
+                    _ => ArgumentError::ArgumentPreprocessingError(
+                        "Error fetching message".to_string(),
+                    ),
+                },
+                _ => {
+                    ArgumentError::ArgumentPreprocessingError("Error fetching message".to_string())
+                }
+            })
+    }
+}
+
+/// Checks a channel belongs to the current guild and the invoking member can read its history,
+/// so messages from hidden channels or other conversations can't be read through the bot.
+/// Outside of guilds only the current channel is readable
+async fn check_readable(ctx: &Context, channel_id: ChannelId) -> Result<(), ArgumentError> {
+    if channel_id == ctx.channel_id() {
+        return Ok(());
+    }
+    let (guild_id, member) = match (ctx.guild_id(), ctx.member()) {
+        (Some(guild_id), Some(member)) => (guild_id, member),
+        _ => {
+            return Err(ArgumentError::InvalidMessageLink(
+                "Only messages from this conversation can be used in direct messages".to_string(),
+            ))
+        }
+    };
+    let channel = match ctx.cache().guild_channel(channel_id).await {
+        Some(channel) => channel,
+        None => match ctx.http().get_channel(channel_id.0).await {
+            Ok(Channel::Guild(channel)) => channel,
+            Ok(_) => {
+                return Err(ArgumentError::InvalidMessageLink(
+                    "Only messages from this server can be used".to_string(),
+                ))
+            }
+            Err(_) => {
+                return Err(ArgumentError::MissingPermissions(format!(
+                    "I am not allowed to access <#{}>",
+                    channel_id
+                )))
+            }
+        },
+    };
+    if channel.guild_id != guild_id {
+        return Err(ArgumentError::InvalidMessageLink(
+            "Message link points to another server".to_string(),
+        ));
+    }
+    let permissions = match ctx.cache().guild(guild_id).await {
+        Some(guild) => guild.user_permissions_in(&channel, member),
+        None => match ctx.http().get_guild(guild_id.0).await {
+            Ok(guild) => guild.user_permissions_in(&channel, member),
+            Err(_) => {
+                return Err(ArgumentError::MissingPermissions(format!(
+                    "I could not check your permissions in <#{}>",
+                    channel_id
+                )))
+            }
+        },
+    }
+    .unwrap_or_else(|_| Permissions::empty());
+    if permissions.contains(Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY) {
+        Ok(())
+    } else {
+        Err(ArgumentError::MissingPermissions(format!(
+            "You are not allowed to read messages in <#{}>",
+            channel_id
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(guild: Option<u64>, channel: Option<u64>, message: u64) -> MessageLink {
+        MessageLink {
+            guild_id: guild.map(GuildId),
+            channel_id: channel.map(ChannelId),
+            message_id: MessageId(message),
+        }
+    }
+
+    #[test]
+    fn parses_ids() {
+        assert_eq!(
+            parse_id(" 381897048103346186 ").unwrap(),
+            381897048103346186
+        );
+        for input in ["", "0", "-1", "abc", "18446744073709551616"] {
+            assert!(matches!(parse_id(input), Err(ArgumentError::InvalidId(_))));
+        }
+    }
+
+    #[test]
+    fn parses_message_links() {
+        assert_eq!(
+            MessageLink::parse("381897048103346186").unwrap(),
+            link(None, None, 381897048103346186)
+        );
+        assert_eq!(
+            MessageLink::parse("381880193251409931-381897048103346186").unwrap(),
+            link(None, Some(381880193251409931), 381897048103346186)
+        );
+        let expected = link(
+            Some(81384788765712384),
+            Some(381880193251409931),
+            381897048103346186,
+        );
+        for input in [
+            "https://discord.com/channels/81384788765712384/381880193251409931/381897048103346186",
+            "<https://ptb.discord.com/channels/81384788765712384/381880193251409931/381897048103346186>",
+            "canary.discordapp.com/channels/81384788765712384/381880193251409931/381897048103346186/",
+        ] {
+            assert_eq!(MessageLink::parse(input).unwrap(), expected);
+        }
+        assert_eq!(
+            MessageLink::parse(
+                "https://discord.com/channels/@me/381880193251409931/381897048103346186"
+            )
+            .unwrap(),
+            link(None, Some(381880193251409931), 381897048103346186)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_message_links() {
+        for input in [
+            "https://discord.com/channels/81384788765712384/381880193251409931",
+            "https://discord.com/channels/81384788765712384/381880193251409931/abc",
+            "https://discord.com/channels/81384788765712384/0/381897048103346186",
+            "https://discord.com/channels/1/2/3/4",
+        ] {
+            assert!(matches!(
+                MessageLink::parse(input),
+                Err(ArgumentError::InvalidMessageLink(_))
+            ));
+        }
+        for input in ["abc", "1-abc", "https://example.com/channels/1/2/3"] {
+            assert!(matches!(
+                MessageLink::parse(input),
+                Err(ArgumentError::InvalidId(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn finds_link_paths() {
+        assert_eq!(link_path("https://discord.com/channels/1/2"), Some("1/2"));
+        assert_eq!(link_path("discordapp.com/channels/1"), Some("1"));
+        assert_eq!(link_path("https://example.com/channels/1/2"), None);
+        assert_eq!(link_path("381897048103346186"), None);
+    }
+}
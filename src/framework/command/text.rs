@@ -0,0 +1,385 @@
+//! Colours, emojis and links parsed from string options
+//!
+//! - [`Colour`]: `#5865F2`, `5865f2`, `#fff`, `0x5865F2`, `rgb(88, 101, 242)` or a name such as
+//!   `blurple`, `red` or `dark green` (see [`COLOUR_NAMES`])
+//! - [`ReactionType`]: a Unicode emoji such as `👍`, or a custom emoji such as `<:ferris:381880193251409931>`
+//! - [`Url`]: an absolute `http` or `https` link
+
+use crate::framework::command::argument::{
+    expect_string, ArgumentError, AsCommandArgumentValue, CommandArgumentValue,
+    CommandArgumentValueType,
+};
+use crate::Context;
+use async_trait::async_trait;
+use serenity::model::channel::ReactionType;
+use serenity::model::id::EmojiId;
+use serenity::utils::Colour;
+use std::iter::Peekable;
+use std::str::Chars;
+use url::Url;
+
+/// Colour names accepted by [`Colour`] arguments, matched ignoring case, spaces, `-` and `_`
+pub const COLOUR_NAMES: &[(&str, u32)] = &[
+    ("blurple", 0x5865F2),
+    ("white", 0xFFFFFF),
+    ("black", 0x000000),
+    ("grey", 0x95A5A6),
+    ("gray", 0x95A5A6),
+    ("darkgrey", 0x607D8B),
+    ("darkgray", 0x607D8B),
+    ("red", 0xE74C3C),
+    ("darkred", 0x992D22),
+    ("orange", 0xE67E22),
+    ("darkorange", 0xA84300),
+    ("yellow", 0xFEE75C),
+    ("gold", 0xF1C40F),
+    ("darkgold", 0xC27C0E),
+    ("green", 0x2ECC71),
+    ("darkgreen", 0x1F8B4C),
+    ("teal", 0x1ABC9C),
+    ("darkteal", 0x11806A),
+    ("blue", 0x3498DB),
+    ("darkblue", 0x206694),
+    ("purple", 0x9B59B6),
+    ("darkpurple", 0x71368A),
+    ("magenta", 0xE91E63),
+    ("darkmagenta", 0xAD1457),
+    ("pink", 0xEB459E),
+    ("fuchsia", 0xEB459E),
+];
+
+/// Parses a colour, see the [module documentation](self) for accepted formats
+pub fn parse_colour(input: &str) -> Result<Colour, ArgumentError> {
+    let trimmed = input.trim();
+    let invalid = || {
+        ArgumentError::InvalidValue(format!(
+            "`{}` is not a valid colour, try a hex code like `#5865F2`, `rgb(88, 101, 242)` or a name like `blurple`",
+            trimmed
+        ))
+    };
+
+    let name: String = trimmed
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .collect::<String>()
+        .to_lowercase();
+    if let Some((_, value)) = COLOUR_NAMES.iter().find(|(n, _)| *n == name) {
+        return Ok(Colour(*value));
+    }
+
+    if let Some(channels) = name.strip_prefix("rgb(").and_then(|c| c.strip_suffix(')')) {
+        let channels = channels
+            .split(',')
+            .map(|c| c.parse::<u8>())
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| {
+                ArgumentError::InvalidValue(format!(
+                    "`{}` is not a valid colour, each channel of rgb() must be between 0 and 255",
+                    trimmed
+                ))
+            })?;
+        return match channels[..] {
+            [r, g, b] => Ok(Colour::from_rgb(r, g, b)),
+            _ => Err(invalid()),
+        };
+    }
+
+    let hex = name
+        .strip_prefix('#')
+        .or_else(|| name.strip_prefix("0x"))
+        .unwrap_or(&name);
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    match hex.len() {
+        6 => u32::from_str_radix(hex, 16)
+            .map(Colour)
+            .map_err(|_| invalid()),
+        // Shorthand like `#fa0`, each digit is doubled
+        3 => u32::from_str_radix(&hex.chars().flat_map(|c| [c, c]).collect::<String>(), 16)
+            .map(Colour)
+            .map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses a Unicode emoji or a custom emoji such as `<:name:id>` or `<a:name:id>`
+pub fn parse_emoji(input: &str) -> Result<ReactionType, ArgumentError> {
+    let trimmed = input.trim();
+    let invalid = || {
+        ArgumentError::InvalidValue(format!(
+            "`{}` is not an emoji, use a Unicode emoji or a custom emoji from this server",
+            trimmed
+        ))
+    };
+
+    if let Some(custom) = trimmed.strip_prefix('<').and_then(|e| e.strip_suffix('>')) {
+        let (animated, custom) = match custom.strip_prefix('a') {
+            Some(rest) => (true, rest),
+            None => (false, custom),
+        };
+        let mut parts = custom.strip_prefix(':').ok_or_else(invalid)?.split(':');
+        let (name, id) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(name), Some(id), None) if !name.is_empty() => (name, id),
+            _ => return Err(invalid()),
+        };
+        let id = id.parse::<u64>().map_err(|_| invalid())?;
+        return Ok(ReactionType::Custom {
+            animated,
+            id: EmojiId(id),
+            name: Some(name.to_string()),
+        });
+    }
+
+    if is_unicode_emoji(trimmed) {
+        Ok(ReactionType::Unicode(trimmed.to_string()))
+    } else {
+        Err(invalid())
+    }
+}
+
+const VARIATION_SELECTOR: char = '\u{FE0F}';
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+const KEYCAP: char = '\u{20E3}';
+const BLACK_FLAG: char = '\u{1F3F4}';
+const CANCEL_TAG: char = '\u{E007F}';
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+fn is_skin_tone(c: char) -> bool {
+    ('\u{1F3FB}'..='\u{1F3FF}').contains(&c)
+}
+
+fn is_tag(c: char) -> bool {
+    ('\u{E0020}'..='\u{E007E}').contains(&c)
+}
+
+/// Checks if a character is in one of the blocks holding emojis
+fn is_pictographic(c: char) -> bool {
+    let pictographic = matches!(c as u32,
+        0xA9 | 0xAE | 0x203C | 0x2049 | 0x2122 | 0x2139 | 0x2194..=0x2199 | 0x21A9..=0x21AA
+        | 0x231A..=0x231B | 0x2328 | 0x23CF | 0x23E9..=0x23F3 | 0x23F8..=0x23FA | 0x24C2
+        | 0x25AA..=0x25AB | 0x25B6 | 0x25C0 | 0x25FB..=0x25FE | 0x2600..=0x27BF
+        | 0x2934..=0x2935 | 0x2B05..=0x2B07 | 0x2B1B..=0x2B1C | 0x2B50 | 0x2B55 | 0x3030
+        | 0x303D | 0x3297 | 0x3299 | 0x1F000..=0x1FAFF
+    );
+    pictographic && !is_regional_indicator(c) && !is_skin_tone(c)
+}
+
+/// Consumes a single emoji: a keycap like `1️⃣`, a flag made of two regional indicators, or a
+/// pictograph with an optional variation selector, skin tone or tag sequence (ie subdivision flags)
+fn take_emoji(chars: &mut Peekable<Chars>) -> bool {
+    let take = |chars: &mut Peekable<Chars>, expected: fn(char) -> bool| {
+        chars.next_if(|c| expected(*c)).is_some()
+    };
+    match chars.next() {
+        Some(c) if c.is_ascii_digit() || c == '#' || c == '*' => {
+            take(chars, |c| c == VARIATION_SELECTOR);
+            take(chars, |c| c == KEYCAP)
+        }
+        Some(c) if is_regional_indicator(c) => take(chars, is_regional_indicator),
+        Some(c) if is_pictographic(c) => {
+            take(chars, |c| c == VARIATION_SELECTOR);
+            take(chars, is_skin_tone);
+            if c == BLACK_FLAG && take(chars, is_tag) {
+                while take(chars, is_tag) {}
+                return take(chars, |c| c == CANCEL_TAG);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Checks if the input is a single Unicode emoji, possibly made of several emojis joined with
+/// zero width joiners (ie families or professions)
+fn is_unicode_emoji(input: &str) -> bool {
+    let mut chars = input.chars().peekable();
+    loop {
+        if !take_emoji(&mut chars) {
+            return false;
+        }
+        match chars.next() {
+            None => return true,
+            Some(ZERO_WIDTH_JOINER) => (),
+            Some(_) => return false,
+        }
+    }
+}
+
+/// Parses an absolute `http` or `https` URL
+pub fn parse_url(input: &str) -> Result<Url, ArgumentError> {
+    let trimmed = input.trim().trim_start_matches('<').trim_end_matches('>');
+    let url = Url::parse(trimmed).map_err(|e| {
+        ArgumentError::InvalidValue(format!("`{}` is not a valid link: {}", trimmed, e))
+    })?;
+    match url.scheme() {
+        "http" | "https" if url.host().is_some() => Ok(url),
+        _ => Err(ArgumentError::InvalidValue(format!(
+            "`{}` is not a web link, links must start with `https://`",
+            trimmed
+        ))),
+    }
+}
+
+#[async_trait]
+impl AsCommandArgumentValue for Colour {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::String
+    }
+
+    async fn from_returned_argument(
+        _ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError> {
+        parse_colour(&expect_string(arg)?)
+    }
+}
+
+#[async_trait]
+impl AsCommandArgumentValue for ReactionType {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::String
+    }
+
+    async fn from_returned_argument(
+        _ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError> {
+        parse_emoji(&expect_string(arg)?)
+    }
+}
+
+#[async_trait]
+impl AsCommandArgumentValue for Url {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::String
+    }
+
+    async fn from_returned_argument(
+        _ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError> {
+        parse_url(&expect_string(arg)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_colours() {
+        for input in [
+            "#5865F2",
+            "5865f2",
+            "0x5865F2",
+            " rgb(88, 101, 242) ",
+            "Blurple",
+        ] {
+            assert_eq!(parse_colour(input).unwrap(), Colour(0x5865F2), "{}", input);
+        }
+        assert_eq!(parse_colour("#fa0").unwrap(), Colour(0xFFAA00));
+        assert_eq!(parse_colour("dark-green").unwrap(), Colour(0x1F8B4C));
+    }
+
+    #[test]
+    fn rejects_invalid_colours() {
+        for input in [
+            "",
+            "#12345",
+            "#ggg",
+            "rgb(256, 0, 0)",
+            "rgb(1, 2)",
+            "ultraviolet",
+            "#ÿÿÿ",
+        ] {
+            assert!(
+                matches!(parse_colour(input), Err(ArgumentError::InvalidValue(_))),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn parses_emojis() {
+        for input in [
+            "👍",
+            "👍🏽",
+            "❤️",
+            "☺",
+            "1️⃣",
+            "#⃣",
+            "🇫🇷",
+            "👨‍👩‍👧",
+            "🏳️‍🌈",
+            "🧑🏻‍💻",
+            "🏴󠁧󠁢󠁳󠁣󠁴󠁿",
+        ] {
+            assert!(
+                matches!(parse_emoji(input), Ok(ReactionType::Unicode(e)) if e == input),
+                "{}",
+                input
+            );
+        }
+        match parse_emoji(" <a:ferris:381880193251409931> ").unwrap() {
+            ReactionType::Custom { animated, id, name } => {
+                assert!(animated);
+                assert_eq!(id, EmojiId(381880193251409931));
+                assert_eq!(name.as_deref(), Some("ferris"));
+            }
+            other => panic!("Expected a custom emoji, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_emojis() {
+        for input in [
+            "",
+            "a",
+            "1",
+            "é",
+            "日本",
+            "→",
+            "👍👍",
+            "👍a",
+            "🏽",
+            "🇫",
+            "\u{200D}👍",
+            "👍\u{200D}",
+            "🏴\u{E0067}",
+            "<:ferris>",
+            "<:ferris:abc>",
+            "<::381880193251409931>",
+        ] {
+            assert!(
+                matches!(parse_emoji(input), Err(ArgumentError::InvalidValue(_))),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn parses_urls() {
+        assert_eq!(
+            parse_url("<https://example.com/a?b=c>").unwrap().as_str(),
+            "https://example.com/a?b=c"
+        );
+        for input in [
+            "example.com",
+            "ftp://example.com",
+            "javascript:alert(1)",
+            "https://",
+        ] {
+            assert!(
+                matches!(parse_url(input), Err(ArgumentError::InvalidValue(_))),
+                "{}",
+                input
+            );
+        }
+    }
+}
@@ -0,0 +1,706 @@
+//! Parsers for human friendly durations and dates, used by [`Duration`] and [`DateTime<Utc>`] arguments
+//!
+//! # Durations
+//! A duration is a sequence of amounts with units, optionally separated by spaces, commas or `and`:
+//! `90s`, `1h30m`, `2 days, 4 hours`, `1.5h`.
+//!
+//! | Unit    | Accepted forms                          |
+//! |---------|-----------------------------------------|
+//! | Seconds | `s`, `sec`, `secs`, `second`, `seconds` |
+//! | Minutes | `m`, `min`, `mins`, `minute`, `minutes` |
+//! | Hours   | `h`, `hr`, `hrs`, `hour`, `hours`       |
+//! | Days    | `d`, `day`, `days`                      |
+//! | Weeks   | `w`, `wk`, `wks`, `week`, `weeks`       |
+//!
+//! # Dates and times
+//! Dates and times are interpreted in UTC, unless a time zone is given at the end
+//! (`UTC`, `Z`, an offset like `+02:00`, or with the `chrono-tz` feature a name like `Europe/Berlin`).
+//! - `now`
+//! - RFC 3339 timestamps: `2022-01-31T17:00:00Z`
+//! - Relative times: `in 2h30m`, `3 days ago`, `1w from now`
+//! - A day, a time, or both: `tomorrow 5pm`, `friday at 9:30am`, `2022-01-31 17:00 +01:00`, `noon`
+//!
+//! Days are `today`, `tomorrow`, `yesterday`, a weekday (the next one, not today) or a `YYYY-MM-DD` date.
+//! Times are `17:30`, `17:30:15`, `5pm`, `5:30 pm`, `noon` or `midnight`. A day without a time means
+//! the start of that day, a time without a day means its next occurrence.
+
+use crate::framework::command::argument::{
+    expect_string, ArgumentError, AsCommandArgumentValue, CommandArgumentOptions,
+    CommandArgumentValue, CommandArgumentValueType,
+};
+use crate::Context;
+use async_trait::async_trait;
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, FixedOffset, NaiveDate, NaiveTime, Offset,
+    TimeZone, Utc, Weekday,
+};
+use std::fmt::Display;
+use std::time::Duration;
+
+/// Error parsing or validating a duration or a date
+#[derive(Debug, Clone)]
+pub enum TimeError {
+    /// Nothing was provided
+    Empty,
+    /// An amount could not be parsed (ie `1.2.3h`)
+    InvalidNumber(String),
+    /// An amount was given without a unit (ie `5`)
+    MissingUnit(String),
+    /// A duration unit was not recognized
+    UnknownUnit(String),
+    /// A date or time could not be understood
+    InvalidDateTime(String),
+    /// A time zone was not recognized
+    UnknownTimeZone(String),
+    /// Value is below the `min` bound of the option
+    TooSmall(String),
+    /// Value is above the `max` bound of the option
+    TooLarge(String),
+    /// A duration or date is too far away to be represented
+    OutOfRange(String),
+    /// A `min` or `max` bound of the option could not be parsed
+    InvalidBound(String),
+}
+
+impl Display for TimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeError::Empty => write!(f, "No time was provided"),
+            TimeError::InvalidNumber(n) => write!(f, "`{}` is not a valid amount", n),
+            TimeError::MissingUnit(n) => write!(f, "`{}` needs a unit (ie `{}m`)", n, n),
+            TimeError::UnknownUnit(u) => write!(
+                f,
+                "Unknown unit `{}`, expected s, m, h, d or w (ie `1h30m`)",
+                u
+            ),
+            TimeError::InvalidDateTime(s) => write!(
+                f,
+                "Could not understand `{}`, try `tomorrow 5pm`, `in 2h` or `2022-01-31 17:00`",
+                s
+            ),
+            TimeError::UnknownTimeZone(tz) => write!(f, "Unknown time zone `{}`", tz),
+            TimeError::TooSmall(min) => write!(f, "Must be at least {}", min),
+            TimeError::TooLarge(max) => write!(f, "Must be at most {}", max),
+            TimeError::OutOfRange(s) => write!(f, "`{}` is too far away", s),
+            TimeError::InvalidBound(b) => write!(f, "The bound `{}` of this option is invalid", b),
+        }
+    }
+}
+
+impl std::error::Error for TimeError {}
+
+impl TimeError {
+    /// Fluent message id used to translate this error with the `i18n` feature
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            TimeError::Empty => "ataraxy-time-empty",
+            TimeError::InvalidNumber(_) => "ataraxy-time-invalid-number",
+            TimeError::MissingUnit(_) => "ataraxy-time-missing-unit",
+            TimeError::UnknownUnit(_) => "ataraxy-time-unknown-unit",
+            TimeError::InvalidDateTime(_) => "ataraxy-time-invalid-date",
+            TimeError::UnknownTimeZone(_) => "ataraxy-time-unknown-zone",
+            TimeError::TooSmall(_) => "ataraxy-time-too-small",
+            TimeError::TooLarge(_) => "ataraxy-time-too-large",
+            TimeError::OutOfRange(_) => "ataraxy-time-out-of-range",
+            TimeError::InvalidBound(_) => "ataraxy-time-invalid-bound",
+        }
+    }
+
+    /// Arguments of the Fluent message of this error: the rejected `value`, `unit` or `bound`
+    pub fn message_args(&self) -> Vec<(&'static str, String)> {
+        match self {
+            TimeError::Empty => vec![],
+            TimeError::InvalidNumber(value)
+            | TimeError::MissingUnit(value)
+            | TimeError::InvalidDateTime(value)
+            | TimeError::UnknownTimeZone(value)
+            | TimeError::OutOfRange(value) => vec![("value", value.clone())],
+            TimeError::UnknownUnit(unit) => vec![("unit", unit.clone())],
+            TimeError::TooSmall(bound)
+            | TimeError::TooLarge(bound)
+            | TimeError::InvalidBound(bound) => {
+                vec![("bound", bound.clone())]
+            }
+        }
+    }
+}
+
+fn unit_seconds(unit: &str) -> Option<u64> {
+    Some(match unit {
+        "s" | "sec" | "secs" | "second" | "seconds" => 1,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+        "d" | "day" | "days" => 60 * 60 * 24,
+        "w" | "wk" | "wks" | "week" | "weeks" => 60 * 60 * 24 * 7,
+        _ => return None,
+    })
+}
+
+/// Parses a duration such as `1h30m` or `2 days and 4 hours`
+pub fn parse_duration(input: &str) -> Result<Duration, TimeError> {
+    let input = input.trim().to_lowercase();
+    let mut rest = input.as_str();
+    let mut total = 0.0;
+    let mut components = 0;
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if let Some(r) = rest.strip_prefix("and ") {
+            rest = r;
+            continue;
+        }
+        if rest.is_empty() {
+            break;
+        }
+
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (number, r) = rest.split_at(number_end);
+        let r = r.trim_start();
+        let unit_end = r.find(|c: char| !c.is_alphabetic()).unwrap_or(r.len());
+        let (unit, r) = r.split_at(unit_end);
+
+        if number.is_empty() {
+            return Err(TimeError::InvalidNumber(unit.to_string()));
+        }
+        if unit.is_empty() {
+            return Err(TimeError::MissingUnit(number.to_string()));
+        }
+        let amount: f64 = number
+            .parse()
+            .map_err(|_| TimeError::InvalidNumber(number.to_string()))?;
+        let seconds = unit_seconds(unit).ok_or_else(|| TimeError::UnknownUnit(unit.to_string()))?;
+
+        total += amount * seconds as f64;
+        components += 1;
+        rest = r;
+    }
+
+    if components == 0 {
+        return Err(TimeError::Empty);
+    }
+    // `u64::MAX as f64` rounds up to 2^64, which is already out of range
+    if !total.is_finite() || total >= u64::MAX as f64 {
+        return Err(TimeError::OutOfRange(input.to_string()));
+    }
+    Ok(Duration::from_secs_f64(total))
+}
+
+/// Formats a duration with its largest units, ie `1d 2h 30m`
+pub fn format_duration(duration: Duration) -> String {
+    let mut seconds = duration.as_secs();
+    if seconds == 0 {
+        return "0s".to_string();
+    }
+    let mut parts = Vec::new();
+    for (unit, size) in [
+        ("w", 604800),
+        ("d", 86400),
+        ("h", 3600),
+        ("m", 60),
+        ("s", 1),
+    ] {
+        if seconds >= size {
+            parts.push(format!("{}{}", seconds / size, unit));
+            seconds %= size;
+        }
+    }
+    parts.join(" ")
+}
+
+/// Time zone a date is interpreted in
+enum Zone {
+    Fixed(FixedOffset),
+    #[cfg(feature = "chrono-tz")]
+    Named(chrono_tz::Tz),
+}
+
+fn parse_zone(token: &str) -> Option<Zone> {
+    match token.to_lowercase().as_str() {
+        "utc" | "gmt" | "z" => return Some(Zone::Fixed(Utc.fix())),
+        _ => (),
+    }
+
+    let offset = token
+        .strip_prefix("utc")
+        .or_else(|| token.strip_prefix("UTC"))
+        .or_else(|| token.strip_prefix("gmt"))
+        .or_else(|| token.strip_prefix("GMT"))
+        .unwrap_or(token);
+    if let Some(sign) = offset.chars().next().filter(|c| *c == '+' || *c == '-') {
+        let digits: String = offset[1..].chars().filter(|c| *c != ':').collect();
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let (hours, minutes) = match digits.len() {
+            1 | 2 => (digits.parse::<i32>().ok()?, 0),
+            4 => (digits[..2].parse().ok()?, digits[2..].parse().ok()?),
+            _ => return None,
+        };
+        if hours > 14 || minutes > 59 {
+            return None;
+        }
+        let seconds = (hours * 3600 + minutes * 60) * if sign == '-' { -1 } else { 1 };
+        return FixedOffset::east_opt(seconds).map(Zone::Fixed);
+    }
+
+    #[cfg(feature = "chrono-tz")]
+    if let Ok(tz) = token.parse::<chrono_tz::Tz>() {
+        return Some(Zone::Named(tz));
+    }
+    None
+}
+
+fn parse_weekday(token: &str) -> Option<Weekday> {
+    Some(match token {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn parse_time(token: &str) -> Option<NaiveTime> {
+    match token {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => (),
+    }
+    let (clock, meridiem) = if let Some(t) = token.strip_suffix("am") {
+        (t, Some(false))
+    } else if let Some(t) = token.strip_suffix("pm") {
+        (t, Some(true))
+    } else {
+        (token, None)
+    };
+
+    let mut parts = clock.split(':');
+    let hour: u32 = parts.next()?.parse().ok()?;
+    let minute: u32 = parts.next().map(str::parse).unwrap_or(Ok(0)).ok()?;
+    let second: u32 = parts.next().map(str::parse).unwrap_or(Ok(0)).ok()?;
+    if parts.next().is_some() || (meridiem.is_none() && !clock.contains(':')) {
+        return None;
+    }
+
+    let hour = match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+/// Parses a date and time relative to `now`, see the [module documentation](self) for the grammar
+pub fn parse_datetime(input: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, TimeError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(TimeError::Empty);
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(trimmed) {
+        return Ok(date.with_timezone(&Utc));
+    }
+
+    let lower = trimmed.to_lowercase();
+    if lower == "now" {
+        return Ok(now);
+    }
+    let relative = |duration: &str, future: bool| -> Result<DateTime<Utc>, TimeError> {
+        let out_of_range = || TimeError::OutOfRange(trimmed.to_string());
+        let duration =
+            ChronoDuration::from_std(parse_duration(duration)?).map_err(|_| out_of_range())?;
+        if future {
+            now.checked_add_signed(duration).ok_or_else(out_of_range)
+        } else {
+            now.checked_sub_signed(duration).ok_or_else(out_of_range)
+        }
+    };
+    if let Some(duration) = lower.strip_prefix("in ") {
+        return relative(duration, true);
+    }
+    if let Some(duration) = lower.strip_suffix(" from now") {
+        return relative(duration, true);
+    }
+    if let Some(duration) = lower.strip_suffix(" ago") {
+        return relative(duration, false);
+    }
+
+    let mut tokens: Vec<&str> = trimmed.split_whitespace().collect();
+    let zone = match tokens.last().and_then(|t| parse_zone(t)) {
+        Some(zone) => {
+            tokens.pop();
+            zone
+        }
+        None => Zone::Fixed(Utc.fix()),
+    };
+
+    match zone {
+        Zone::Fixed(offset) => resolve(&tokens, trimmed, now, &offset),
+        #[cfg(feature = "chrono-tz")]
+        Zone::Named(tz) => resolve(&tokens, trimmed, now, &tz),
+    }
+}
+
+/// Resolves the day and time tokens of a date in the given time zone
+fn resolve<Tz: TimeZone>(
+    tokens: &[&str],
+    input: &str,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Result<DateTime<Utc>, TimeError> {
+    let invalid = || TimeError::InvalidDateTime(input.to_string());
+    let today = now.with_timezone(tz).naive_local().date();
+
+    let mut date: Option<NaiveDate> = None;
+    let mut time: Option<NaiveTime> = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i].to_lowercase();
+        // Allow a space before am/pm, ie `5 pm`
+        let merged = match tokens.get(i + 1).map(|t| t.to_lowercase()) {
+            Some(next) if next == "am" || next == "pm" => {
+                i += 1;
+                format!("{}{}", token, next)
+            }
+            _ => token,
+        };
+        i += 1;
+
+        if merged == "at" || merged == "on" {
+            continue;
+        }
+        let day = match merged.as_str() {
+            "today" => Some(today),
+            "tomorrow" => today.succ_opt(),
+            "yesterday" => today.pred_opt(),
+            _ => parse_weekday(&merged)
+                .map(|weekday| {
+                    let ahead = (7 + weekday.num_days_from_monday()
+                        - today.weekday().num_days_from_monday())
+                        % 7;
+                    today + ChronoDuration::days(if ahead == 0 { 7 } else { ahead } as i64)
+                })
+                .or_else(|| NaiveDate::parse_from_str(&merged, "%Y-%m-%d").ok()),
+        };
+        if let Some(day) = day {
+            if date.replace(day).is_some() {
+                return Err(invalid());
+            }
+        } else if let Some(t) = parse_time(&merged) {
+            if time.replace(t).is_some() {
+                return Err(invalid());
+            }
+        } else {
+            return Err(invalid());
+        }
+    }
+
+    let to_utc = |date: NaiveDate, time: NaiveTime| {
+        tz.from_local_datetime(&date.and_time(time))
+            .earliest()
+            .map(|d| d.with_timezone(&Utc))
+            .ok_or_else(invalid)
+    };
+    match (date, time) {
+        (Some(date), Some(time)) => to_utc(date, time),
+        (Some(date), None) => to_utc(date, NaiveTime::from_hms_opt(0, 0, 0).ok_or_else(invalid)?),
+        (None, Some(time)) => {
+            let result = to_utc(today, time)?;
+            if result <= now {
+                to_utc(today.succ_opt().ok_or_else(invalid)?, time)
+            } else {
+                Ok(result)
+            }
+        }
+        (None, None) => Err(invalid()),
+    }
+}
+
+#[async_trait]
+impl AsCommandArgumentValue for Duration {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::String
+    }
+
+    async fn from_returned_argument(
+        _ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError> {
+        parse_duration(&expect_string(arg)?).map_err(ArgumentError::InvalidTime)
+    }
+
+    /// Bounds that cannot be parsed reject every value, so a typo does not disable validation
+    fn validate(&self, options: &CommandArgumentOptions) -> Result<(), ArgumentError> {
+        let bound = |b: &Option<String>| {
+            b.as_deref()
+                .map(|b| parse_duration(b).map_err(|_| TimeError::InvalidBound(b.to_string())))
+                .transpose()
+        };
+        if let Some(min) = bound(&options.min_text).map_err(ArgumentError::InvalidTime)? {
+            if *self < min {
+                return Err(ArgumentError::InvalidTime(TimeError::TooSmall(
+                    format_duration(min),
+                )));
+            }
+        }
+        if let Some(max) = bound(&options.max_text).map_err(ArgumentError::InvalidTime)? {
+            if *self > max {
+                return Err(ArgumentError::InvalidTime(TimeError::TooLarge(
+                    format_duration(max),
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsCommandArgumentValue for DateTime<Utc> {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::String
+    }
+
+    async fn from_returned_argument(
+        _ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError> {
+        parse_datetime(&expect_string(arg)?, Utc::now()).map_err(ArgumentError::InvalidTime)
+    }
+
+    /// Bounds are parsed relative to the current time, ie `min = "now", max = "in 28d"`.
+    /// Bounds that cannot be parsed reject every value, so a typo does not disable validation
+    fn validate(&self, options: &CommandArgumentOptions) -> Result<(), ArgumentError> {
+        let now = Utc::now();
+        let bound = |b: &Option<String>| {
+            b.as_deref()
+                .map(|b| match parse_datetime(b, now) {
+                    Ok(date) => Ok((b.to_string(), date)),
+                    Err(_) => Err(ArgumentError::InvalidTime(TimeError::InvalidBound(
+                        b.to_string(),
+                    ))),
+                })
+                .transpose()
+        };
+        if let Some((text, min)) = bound(&options.min_text)? {
+            if *self < min {
+                return Err(ArgumentError::InvalidTime(TimeError::TooSmall(text)));
+            }
+        }
+        if let Some((text, max)) = bound(&options.max_text)? {
+            if *self > max {
+                return Err(ArgumentError::InvalidTime(TimeError::TooLarge(text)));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "chrono-tz")]
+#[async_trait]
+impl AsCommandArgumentValue for chrono_tz::Tz {
+    fn value_type() -> CommandArgumentValueType {
+        CommandArgumentValueType::String
+    }
+
+    async fn from_returned_argument(
+        _ctx: &Context,
+        arg: Option<CommandArgumentValue>,
+    ) -> Result<Self, ArgumentError> {
+        let tz = expect_string(arg)?;
+        tz.trim()
+            .parse::<chrono_tz::Tz>()
+            .map_err(|_| ArgumentError::InvalidTime(TimeError::UnknownTimeZone(tz)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Monday 2022-01-31 12:00 UTC
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2022-01-31T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn date(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(
+            parse_duration("2 days, 4 hours").unwrap(),
+            Duration::from_secs(2 * 86400 + 4 * 3600)
+        );
+        assert_eq!(
+            parse_duration("1 week and 1 minute").unwrap(),
+            Duration::from_secs(604800 + 60)
+        );
+        assert_eq!(parse_duration("1.5H").unwrap(), Duration::from_secs(5400));
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        assert!(matches!(parse_duration(""), Err(TimeError::Empty)));
+        assert!(matches!(
+            parse_duration("5"),
+            Err(TimeError::MissingUnit(_))
+        ));
+        assert!(matches!(
+            parse_duration("5y"),
+            Err(TimeError::UnknownUnit(_))
+        ));
+        assert!(matches!(
+            parse_duration("h"),
+            Err(TimeError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            parse_duration("1.2.3h"),
+            Err(TimeError::InvalidNumber(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_durations_out_of_range() {
+        assert!(matches!(
+            parse_duration("99999999999999999w"),
+            Err(TimeError::OutOfRange(_))
+        ));
+        let huge = format!("{}s", "9".repeat(400));
+        assert!(matches!(
+            parse_duration(&huge),
+            Err(TimeError::OutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(Duration::from_secs(0)), "0s");
+        assert_eq!(format_duration(Duration::from_secs(90061)), "1d 1h 1m 1s");
+        assert_eq!(format_duration(Duration::from_secs(1209600)), "2w");
+    }
+
+    #[test]
+    fn parses_absolute_dates() {
+        assert_eq!(
+            parse_datetime("2022-02-01T17:00:00Z", now()).unwrap(),
+            date("2022-02-01T17:00:00Z")
+        );
+        assert_eq!(
+            parse_datetime("2022-02-01 17:00 +01:00", now()).unwrap(),
+            date("2022-02-01T16:00:00Z")
+        );
+        assert_eq!(
+            parse_datetime("2022-02-01 utc-0530", now()).unwrap(),
+            date("2022-02-01T05:30:00Z")
+        );
+        assert_eq!(parse_datetime("now", now()).unwrap(), now());
+    }
+
+    #[test]
+    fn parses_relative_dates() {
+        assert_eq!(
+            parse_datetime("in 2h30m", now()).unwrap(),
+            date("2022-01-31T14:30:00Z")
+        );
+        assert_eq!(
+            parse_datetime("3 days ago", now()).unwrap(),
+            date("2022-01-28T12:00:00Z")
+        );
+        assert_eq!(
+            parse_datetime("1w from now", now()).unwrap(),
+            date("2022-02-07T12:00:00Z")
+        );
+    }
+
+    #[test]
+    fn parses_days_and_times() {
+        assert_eq!(
+            parse_datetime("tomorrow 5pm", now()).unwrap(),
+            date("2022-02-01T17:00:00Z")
+        );
+        assert_eq!(
+            parse_datetime("friday at 9:30 am", now()).unwrap(),
+            date("2022-02-04T09:30:00Z")
+        );
+        // The same weekday means next week, a past time means tomorrow
+        assert_eq!(
+            parse_datetime("monday", now()).unwrap(),
+            date("2022-02-07T00:00:00Z")
+        );
+        assert_eq!(
+            parse_datetime("11:00", now()).unwrap(),
+            date("2022-02-01T11:00:00Z")
+        );
+        assert_eq!(
+            parse_datetime("noon Z", now()).unwrap(),
+            date("2022-02-01T12:00:00Z")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for input in [
+            "",
+            "someday",
+            "tomorrow today",
+            "5pm 6pm",
+            "13pm",
+            "25:00",
+            "2022-02-30",
+            "tomorrow +1é2",
+            "tomorrow +99:00",
+        ] {
+            assert!(parse_datetime(input, now()).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_relative_dates_out_of_range() {
+        assert!(matches!(
+            parse_datetime("in 100000000w", now()),
+            Err(TimeError::OutOfRange(_))
+        ));
+        assert!(matches!(
+            parse_datetime("100000000w ago", now()),
+            Err(TimeError::OutOfRange(_))
+        ));
+        assert!(matches!(
+            parse_datetime("in 99999999999999999w", now()),
+            Err(TimeError::OutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_unparsable_bounds() {
+        let options = CommandArgumentOptions {
+            min: None,
+            max: None,
+            min_text: Some("1 mnute".to_string()),
+            max_text: None,
+            min_len: None,
+            max_len: None,
+            channel_type: None,
+            content_types: None,
+            max_size: None,
+            regex: None,
+            choices: None,
+        };
+        assert!(matches!(
+            AsCommandArgumentValue::validate(&Duration::from_secs(60), &options),
+            Err(ArgumentError::InvalidTime(TimeError::InvalidBound(_)))
+        ));
+        assert!(matches!(
+            AsCommandArgumentValue::validate(&now(), &options),
+            Err(ArgumentError::InvalidTime(TimeError::InvalidBound(_)))
+        ));
+    }
+}
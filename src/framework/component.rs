@@ -0,0 +1,499 @@
+//! Routing of message component interactions (buttons and select menus) by `custom_id`
+//!
+//! Handlers are registered with [`Framework::component`](crate::Framework::component) and match
+//! custom ids against a pattern such as `vote:{id}`, where `{id}` captures the text up to the next
+//! literal part of the pattern (or the rest of the id for a trailing capture). Since the state of a
+//! component lives in its custom id, components keep working after the bot restarts.
+
+use crate::framework::command::argument::ArgumentError;
+use crate::framework::context;
+use crate::framework::custom_id::{CustomIdCodec, CustomIdError, Payload};
+use crate::framework::DataMap;
+use serenity::cache::Cache;
+use serenity::http::{CacheHttp, Http};
+use serenity::model::guild::Member;
+use serenity::model::id::{ChannelId, GuildId};
+use serenity::model::interactions::message_component::MessageComponentInteraction;
+use serenity::model::interactions::InteractionResponseType;
+use serenity::model::prelude::InteractionApplicationCommandCallbackDataFlags;
+use serenity::model::user::User;
+use serenity::prelude::Context as SerenityContext;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Values captured from a custom id, keyed by capture name
+pub type Captures = HashMap<String, String>;
+
+type Handler = for<'a> fn(
+    ComponentContext,
+    &'a Captures,
+) -> Pin<Box<dyn Future<Output = Result<(), ArgumentError>> + Send>>;
+
+#[derive(Clone)]
+pub struct ComponentHandler(pub Handler);
+
+impl Debug for ComponentHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Component Handler")
+    }
+}
+
+/// A component handler, created with the [`component`](crate::component) macro
+#[derive(Debug, Clone)]
+pub struct Component {
+    pub pattern: ComponentPattern,
+    pub action: ComponentHandler,
+}
+
+pub trait IntoComponent {
+    fn into_component(self) -> Component;
+}
+
+impl IntoComponent for Component {
+    fn into_component(self) -> Component {
+        self
+    }
+}
+
+impl<T: Fn() -> Component> IntoComponent for T {
+    fn into_component(self) -> Component {
+        self()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Capture(String),
+}
+
+/// Pattern matched against custom ids, ie `vote:{id}` or `page:{list}:{page}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentPattern {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl ComponentPattern {
+    /// Parses a pattern. Captures are written `{name}` and must be separated by literal text
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut rest = pattern;
+        while !rest.is_empty() {
+            match rest.find('{') {
+                Some(0) => {
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| format!("Unclosed capture in `{}`", pattern))?;
+                    let name = &rest[1..end];
+                    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                        return Err(format!("Invalid capture name `{}` in `{}`", name, pattern));
+                    }
+                    if let Some(Segment::Capture(_)) = segments.last() {
+                        return Err(format!(
+                            "Captures must be separated by literal text in `{}`",
+                            pattern
+                        ));
+                    }
+                    if segments.contains(&Segment::Capture(name.to_string())) {
+                        return Err(format!("Duplicate capture `{}` in `{}`", name, pattern));
+                    }
+                    segments.push(Segment::Capture(name.to_string()));
+                    rest = &rest[end + 1..];
+                }
+                Some(start) => {
+                    segments.push(Segment::Literal(rest[..start].to_string()));
+                    rest = &rest[start..];
+                }
+                None => {
+                    segments.push(Segment::Literal(rest.to_string()));
+                    rest = "";
+                }
+            }
+        }
+        if segments
+            .iter()
+            .any(|s| matches!(s, Segment::Literal(l) if l.contains('}')))
+        {
+            return Err(format!("Unopened capture in `{}`", pattern));
+        }
+        Ok(Self {
+            source: pattern.to_string(),
+            segments,
+        })
+    }
+
+    /// Names of the captures, in order
+    pub fn captures(&self) -> Vec<&str> {
+        self.segments
+            .iter()
+            .filter_map(|s| match s {
+                Segment::Capture(name) => Some(name.as_str()),
+                Segment::Literal(_) => None,
+            })
+            .collect()
+    }
+
+    /// Matches a custom id, returning the captured values
+    pub fn matches(&self, custom_id: &str) -> Option<Captures> {
+        let mut captures = HashMap::new();
+        let mut rest = custom_id;
+        let mut segments = self.segments.iter().peekable();
+        while let Some(segment) = segments.next() {
+            match segment {
+                Segment::Literal(literal) => rest = rest.strip_prefix(literal.as_str())?,
+                Segment::Capture(name) => {
+                    let end = match segments.peek() {
+                        Some(Segment::Literal(next)) => rest.find(next.as_str())?,
+                        _ => rest.len(),
+                    };
+                    if end == 0 {
+                        return None;
+                    }
+                    captures.insert(name.clone(), rest[..end].to_string());
+                    rest = &rest[end..];
+                }
+            }
+        }
+        if rest.is_empty() {
+            Some(captures)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Parses a captured value for a typed handler parameter
+pub fn parse_capture<T: std::str::FromStr>(
+    captures: &Captures,
+    name: &str,
+) -> Result<T, ArgumentError> {
+    captures
+        .get(name)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| ArgumentError::InvalidValue(format!("Invalid `{}` in component id", name)))
+}
+
+/// Context of a button click or select menu interaction
+#[derive(Clone)]
+pub struct ComponentContext {
+    serenity_context: SerenityContext,
+    interaction: MessageComponentInteraction,
+    data: Arc<DataMap>,
+    custom_ids: Option<Arc<CustomIdCodec>>,
+}
+
+impl ComponentContext {
+    /// Creates a context outside of the framework, which cannot decode payloads since no
+    /// custom id secret is known
+    pub fn new(
+        serenity_context: &SerenityContext,
+        interaction: &MessageComponentInteraction,
+        data: Arc<DataMap>,
+    ) -> Self {
+        Self::from_framework(serenity_context, interaction, data, None)
+    }
+
+    pub(crate) fn from_framework(
+        serenity_context: &SerenityContext,
+        interaction: &MessageComponentInteraction,
+        data: Arc<DataMap>,
+        custom_ids: Option<Arc<CustomIdCodec>>,
+    ) -> Self {
+        Self {
+            serenity_context: serenity_context.clone(),
+            interaction: interaction.clone(),
+            data,
+            custom_ids,
+        }
+    }
+
+    /// The raw interaction this context was created from
+    pub fn interaction(&self) -> &MessageComponentInteraction {
+        &self.interaction
+    }
+
+    pub fn http(&self) -> &Http {
+        self.serenity_context.http()
+    }
+
+    pub fn cache(&self) -> &Arc<Cache> {
+        &self.serenity_context.cache
+    }
+
+    pub fn serenity_context(&self) -> &SerenityContext {
+        &self.serenity_context
+    }
+
+    /// The custom id of the component that was used
+    pub fn custom_id(&self) -> &str {
+        &self.interaction.data.custom_id
+    }
+
+    /// The selected values, for select menus
+    pub fn values(&self) -> &[String] {
+        &self.interaction.data.values
+    }
+
+    /// The user who used the component
+    pub fn user(&self) -> &User {
+        &self.interaction.user
+    }
+
+    /// The member who used the component, only present in guilds
+    pub fn member(&self) -> Option<&Member> {
+        self.interaction.member.as_ref()
+    }
+
+    pub fn guild_id(&self) -> Option<GuildId> {
+        self.interaction.guild_id
+    }
+
+    pub fn channel_id(&self) -> ChannelId {
+        self.interaction.channel_id
+    }
+
+    /// The selected language of the user who used the component
+    pub fn locale(&self) -> &str {
+        &self.interaction.locale
+    }
+
+    /// The preferred locale of the guild, only present in guilds
+    pub fn guild_locale(&self) -> Option<&str> {
+        self.interaction.guild_locale.as_deref()
+    }
+
+    /// Gets shared data registered with [`Framework::data`](crate::Framework::data)
+    /// # Panics
+    /// Panics if no data of type `T` was registered, see [`ComponentContext::try_data`]
+    pub fn data<T: Send + Sync + 'static>(&self) -> Arc<T> {
+        self.try_data::<T>().unwrap_or_else(|| {
+            panic!(
+                "No data of type {} registered in the framework",
+                std::any::type_name::<T>()
+            )
+        })
+    }
+
+    /// Gets shared data registered with [`Framework::data`](crate::Framework::data), if present
+    pub fn try_data<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.data.get::<T>()
+    }
+
+    /// Locales to translate responses in: the user's locale, then the guild's
+    fn response_locales(&self) -> Vec<&str> {
+        let mut locales = vec![self.locale()];
+        locales.extend(self.guild_locale());
+        locales
+    }
+
+    /// Message reported to the user for an argument error
+    pub(crate) fn error_message(&self, error: &ArgumentError) -> String {
+        context::error_message(&self.data, &self.response_locales(), error)
+    }
+
+    /// Decodes the payload of the custom id, for ids created with [`ComponentContext::encode_custom_id`]
+    /// or [`Context::encode_custom_id`](crate::Context::encode_custom_id)
+    pub fn payload<T: Payload>(&self) -> Result<T, ArgumentError> {
+        let codec = self
+            .custom_ids
+            .as_ref()
+            .ok_or_else(|| ArgumentError::ExtractionError(CustomIdError::NoSecret.to_string()))?;
+        codec.decode(self.custom_id()).map_err(|e| match e {
+            CustomIdError::Outdated { .. } => ArgumentError::InvalidValue(
+                "This component has expired, please use the command again".to_string(),
+            ),
+            _ => ArgumentError::InvalidValue("This component is invalid".to_string()),
+        })
+    }
+
+    /// Encodes a payload into a signed custom id, see [`custom_id`](crate::framework::custom_id)
+    pub fn encode_custom_id<T: Payload>(&self, payload: &T) -> Result<String, CustomIdError> {
+        encode_custom_id(&self.custom_ids, payload)
+    }
+
+    /// Replaces the content of the message the component is attached to
+    pub async fn update<S: Into<String>>(&self, msg: S) -> serenity::Result<()> {
+        self.interaction
+            .create_interaction_response(&self.serenity_context.http, |res| {
+                res.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|data| data.content(msg.into()))
+            })
+            .await
+    }
+
+    /// Acknowledges the interaction without changing the message, so it can be edited later
+    pub async fn defer(&self) -> serenity::Result<()> {
+        self.interaction
+            .create_interaction_response(&self.serenity_context.http, |res| {
+                res.kind(InteractionResponseType::DeferredUpdateMessage)
+            })
+            .await
+    }
+
+    /// Replies with a new message
+    pub async fn reply<S: Into<String>>(&self, msg: S) -> serenity::Result<()> {
+        self.interaction
+            .create_interaction_response(&self.serenity_context.http, |res| {
+                res.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|data| data.content(msg.into()))
+            })
+            .await
+    }
+
+    /// Replies with a new message only visible to the user who used the component
+    pub async fn reply_ephemeral<S: Into<String>>(&self, msg: S) -> serenity::Result<()> {
+        self.interaction
+            .create_interaction_response(&self.serenity_context.http, |res| {
+                res.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|data| {
+                        data.content(msg.into())
+                            .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                    })
+            })
+            .await
+    }
+}
+
+impl CacheHttp for ComponentContext {
+    fn http(&self) -> &Http {
+        self.serenity_context.http()
+    }
+
+    fn cache(&self) -> Option<&Arc<Cache>> {
+        Some(&self.serenity_context.cache)
+    }
+}
+
+/// Encodes a payload with the codec registered with
+/// [`Framework::custom_id_secret`](crate::Framework::custom_id_secret)
+pub(crate) fn encode_custom_id<T: Payload>(
+    codec: &Option<Arc<CustomIdCodec>>,
+    payload: &T,
+) -> Result<String, CustomIdError> {
+    codec
+        .as_ref()
+        .ok_or(CustomIdError::NoSecret)?
+        .encode(payload)
+}
+
+/// Runs the first component handler whose pattern matches the custom id,
+/// reporting errors back to the user. Returns whether a handler matched
+pub(crate) async fn dispatch(components: &[Component], context: ComponentContext) -> bool {
+    let matched = components.iter().find_map(|component| {
+        component
+            .pattern
+            .matches(context.custom_id())
+            .map(|captures| (component, captures))
+    });
+    let (component, captures) = match matched {
+        Some(matched) => matched,
+        None => return false,
+    };
+    if let Err(e) = component.action.0(context.clone(), &captures).await {
+        let message = context.error_message(&e);
+        context.reply_ephemeral(message).await.ok();
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn captured(pattern: &str, custom_id: &str) -> Option<Vec<(String, String)>> {
+        let mut captures: Vec<(String, String)> = ComponentPattern::parse(pattern)
+            .unwrap()
+            .matches(custom_id)?
+            .into_iter()
+            .collect();
+        captures.sort();
+        Some(captures)
+    }
+
+    fn capture(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn parses_patterns() {
+        let pattern = ComponentPattern::parse("poll:{id}:vote-{choice}").unwrap();
+        assert_eq!(pattern.captures(), vec!["id", "choice"]);
+        assert_eq!(pattern.as_str(), "poll:{id}:vote-{choice}");
+        assert!(ComponentPattern::parse("confirm")
+            .unwrap()
+            .captures()
+            .is_empty());
+        assert_eq!(
+            ComponentPattern::parse("{user_1}").unwrap().captures(),
+            vec!["user_1"]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        for pattern in [
+            "poll:{id",
+            "poll:id}",
+            "poll:{}",
+            "poll:{a-b}",
+            "poll:{a}{b}",
+            "poll:{id}:{id}",
+            "poll:{é:}",
+        ] {
+            assert!(ComponentPattern::parse(pattern).is_err(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn matches_custom_ids() {
+        assert_eq!(captured("confirm", "confirm"), Some(vec![]));
+        assert_eq!(
+            captured("poll:{id}:vote-{choice}", "poll:42:vote-yes"),
+            Some(vec![capture("choice", "yes"), capture("id", "42")])
+        );
+        // The last capture takes the rest of the id, including separators
+        assert_eq!(
+            captured("vote:{payload}", "vote:1:abc:[42,\"yes\"]"),
+            Some(vec![capture("payload", "1:abc:[42,\"yes\"]")])
+        );
+        // A capture ends at the first occurrence of the following literal
+        assert_eq!(
+            captured("{a}:{b}", "x:y:z"),
+            Some(vec![capture("a", "x"), capture("b", "y:z")])
+        );
+        assert_eq!(
+            captured("{id}-end", "é-end"),
+            Some(vec![capture("id", "é")])
+        );
+    }
+
+    #[test]
+    fn rejects_other_custom_ids() {
+        for (pattern, custom_id) in [
+            ("confirm", "confirmed"),
+            ("confirm", "confir"),
+            ("poll:{id}", "poll:"),
+            ("poll:{id}", "vote:42"),
+            ("poll:{id}:vote", "poll:42"),
+            ("poll:{id}:vote", "poll:42:vote:"),
+            ("{a}:{b}", ":b"),
+            ("{a}:{b}", "a:"),
+        ] {
+            assert_eq!(
+                captured(pattern, custom_id),
+                None,
+                "{} {}",
+                pattern,
+                custom_id
+            );
+        }
+    }
+}
@@ -1,32 +1,336 @@
+use serenity::cache::Cache;
 use serenity::http::{CacheHttp, Http};
-use serenity::model::interactions::application_command::ApplicationCommandInteraction;
+use serenity::model::channel::{Attachment, Channel};
+use serenity::model::guild::{Member, PartialGuild};
+use serenity::model::id::{ChannelId, GuildId, InteractionId};
+use serenity::model::interactions::application_command::{
+    ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    ApplicationCommandOptionType,
+};
+use serenity::model::interactions::message_component::MessageComponentInteraction;
 use serenity::model::interactions::InteractionResponseType;
 use serenity::model::prelude::InteractionApplicationCommandCallbackDataFlags;
+use serenity::model::user::User;
+use serenity::model::Permissions;
 use serenity::prelude::Context as SerenityContext;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::collector::{Collectors, ComponentCollector};
+use super::component::{self, ComponentContext};
+use super::custom_id::{CustomIdCodec, CustomIdError, Payload};
+use super::modal::{self, Modal, ModalError, ModalSubmit};
+use super::DataMap;
+use crate::framework::command::argument::ArgumentError;
+#[cfg(feature = "i18n")]
+use crate::framework::{TranslationError, Translations};
 
 #[derive(Clone)]
 pub struct Context {
     responded: bool,
     serenity_context: SerenityContext,
     interaction: ApplicationCommandInteraction,
+    data: Arc<DataMap>,
+    collectors: Arc<Collectors>,
+    custom_ids: Option<Arc<CustomIdCodec>>,
 }
 
 impl Context {
+    /// Creates a context outside of the framework. Components and modals can only be awaited,
+    /// and custom ids only encoded, by contexts created by the [`Framework`](crate::Framework)
     pub fn new(
         serenity_context: &SerenityContext,
         interaction: &ApplicationCommandInteraction,
+        data: Arc<DataMap>,
+    ) -> Self {
+        Self::from_framework(
+            serenity_context,
+            interaction,
+            data,
+            Arc::new(Collectors::default()),
+            None,
+        )
+    }
+
+    pub(crate) fn from_framework(
+        serenity_context: &SerenityContext,
+        interaction: &ApplicationCommandInteraction,
+        data: Arc<DataMap>,
+        collectors: Arc<Collectors>,
+        custom_ids: Option<Arc<CustomIdCodec>>,
     ) -> Self {
         Self {
             responded: false,
             serenity_context: serenity_context.clone(),
             interaction: interaction.clone(),
+            data,
+            collectors,
+            custom_ids,
         }
     }
 
+    /// The raw interaction this context was created from
+    pub fn interaction(&self) -> &ApplicationCommandInteraction {
+        &self.interaction
+    }
+
     pub fn http(&self) -> &Http {
         self.serenity_context.http()
     }
 
+    pub fn cache(&self) -> &Arc<Cache> {
+        &self.serenity_context.cache
+    }
+
+    pub fn serenity_context(&self) -> &SerenityContext {
+        &self.serenity_context
+    }
+
+    pub(crate) fn collectors(&self) -> &Collectors {
+        &self.collectors
+    }
+
+    /// The user who invoked the command
+    pub fn user(&self) -> &User {
+        &self.interaction.user
+    }
+
+    /// The member who invoked the command, only present in guilds
+    pub fn member(&self) -> Option<&Member> {
+        self.interaction.member.as_ref()
+    }
+
+    /// Permissions of the invoking member in the channel, only present in guilds
+    pub fn permissions(&self) -> Option<Permissions> {
+        self.interaction.member.as_ref().and_then(|m| m.permissions)
+    }
+
+    pub fn guild_id(&self) -> Option<GuildId> {
+        self.interaction.guild_id
+    }
+
+    pub fn channel_id(&self) -> ChannelId {
+        self.interaction.channel_id
+    }
+
+    pub fn interaction_id(&self) -> InteractionId {
+        self.interaction.id
+    }
+
+    /// Continuation token used to respond to the interaction
+    pub fn token(&self) -> &str {
+        &self.interaction.token
+    }
+
+    /// Path of the invoked command, including subcommand groups and subcommands
+    /// (ie `["settings", "role", "add"]`)
+    pub fn command_path(&self) -> Vec<&str> {
+        let mut path = vec![self.interaction.data.name.as_str()];
+        let mut options: &[ApplicationCommandInteractionDataOption] =
+            &self.interaction.data.options;
+        while let Some(opt) = options.first() {
+            match opt.kind {
+                ApplicationCommandOptionType::SubCommand
+                | ApplicationCommandOptionType::SubCommandGroup => {
+                    path.push(&opt.name);
+                    options = &opt.options;
+                }
+                _ => break,
+            }
+        }
+        path
+    }
+
+    /// The locale selected by the invoking user
+    pub fn locale(&self) -> &str {
+        &self.interaction.locale
+    }
+
+    /// The preferred locale of the guild, only present in guilds
+    pub fn guild_locale(&self) -> Option<&str> {
+        self.interaction.guild_locale.as_deref()
+    }
+
+    /// Locales to translate responses in: the user's locale, then the guild's
+    fn response_locales(&self) -> Vec<&str> {
+        let mut locales = vec![self.locale()];
+        locales.extend(self.guild_locale());
+        locales
+    }
+
+    /// Translates a Fluent message registered with
+    /// [`Framework::translations`](crate::Framework::translations), in the user's locale, then
+    /// the guild's locale, then the default locale. Returns the key if the message is not found
+    /// or fails to format, see [`Context::try_t`] for the error
+    #[cfg(feature = "i18n")]
+    pub fn t(&self, key: &str, args: Option<&fluent_bundle::FluentArgs>) -> String {
+        self.try_t(key, args).unwrap_or_else(|_| key.to_string())
+    }
+
+    /// Translates a Fluent message like [`Context::t`], failing if no translations were
+    /// registered, the message is not found or it fails to format
+    #[cfg(feature = "i18n")]
+    pub fn try_t(
+        &self,
+        key: &str,
+        args: Option<&fluent_bundle::FluentArgs>,
+    ) -> Result<String, TranslationError> {
+        self.try_data::<Translations>()
+            .ok_or_else(|| TranslationError::MissingMessage(key.to_string()))?
+            .format(&self.response_locales(), key, args)
+    }
+
+    /// Message reported to the user for an argument error
+    pub(crate) fn error_message(&self, error: &ArgumentError) -> String {
+        error_message(&self.data, &self.response_locales(), error)
+    }
+
+    /// Encodes a payload into a signed custom id, see [`custom_id`](crate::framework::custom_id).
+    /// Fails with [`CustomIdError::NoSecret`] if no secret was registered with
+    /// [`Framework::custom_id_secret`](crate::Framework::custom_id_secret)
+    pub fn encode_custom_id<T: Payload>(&self, payload: &T) -> Result<String, CustomIdError> {
+        component::encode_custom_id(&self.custom_ids, payload)
+    }
+
+    /// Responds with a modal and waits for the user to submit it, see [`Modal`]
+    /// ```rust, no_run
+    /// # use ataraxy::{Context, Modal};
+    /// # use std::time::Duration;
+    /// #[derive(Modal)]
+    /// #[modal(title = "Report a bug")]
+    /// struct Report {
+    ///     title: String,
+    /// }
+    ///
+    /// # async fn report(ctx: Context) {
+    /// let report = match ctx.show_modal::<Report>(Duration::from_secs(300)).await {
+    ///     Ok(report) => report,
+    ///     Err(_) => return,
+    /// };
+    /// report.reply_ephemeral(format!("Thanks for reporting {}", report.data.title)).await.ok();
+    /// # }
+    /// ```
+    pub async fn show_modal<T: Modal>(
+        &self,
+        timeout: Duration,
+    ) -> Result<ModalSubmit<T>, ModalError> {
+        modal::show(
+            &self.serenity_context,
+            &self.interaction,
+            &self.collectors,
+            &self.data,
+            timeout,
+        )
+        .await
+    }
+
+    /// Waits for the invoking user to use a button or select menu on the reply to this command,
+    /// returning `None` after the timeout. Interactions rejected by `filter` are left to the
+    /// [component handlers](crate::Framework::component)
+    /// ```rust, no_run
+    /// # use ataraxy::Context;
+    /// # use std::time::Duration;
+    /// # async fn delete(ctx: Context) {
+    /// let click = ctx
+    ///     .await_component(|i| i.data.custom_id == "confirm", Duration::from_secs(30))
+    ///     .await;
+    /// if let Some(click) = click {
+    ///     click.update("Confirmed").await.ok();
+    /// }
+    /// # }
+    /// ```
+    pub async fn await_component<F>(&self, filter: F, timeout: Duration) -> Option<ComponentContext>
+    where
+        F: Fn(&MessageComponentInteraction) -> bool + Send + Sync + 'static,
+    {
+        self.collector(filter, true).next(timeout).await
+    }
+
+    /// Collects every use of a button or select menu by the invoking user on the reply to this
+    /// command, until the collector is dropped. Unlike [`Context::await_component`], the collector
+    /// can be created before replying, and keeps collecting while the previous interaction is
+    /// being responded to
+    /// ```rust, no_run
+    /// # use ataraxy::Context;
+    /// # use std::time::Duration;
+    /// # async fn count(ctx: Context) {
+    /// let mut clicks = ctx.collect_components(|i| i.data.custom_id == "count");
+    /// ctx.reply("Click to count").await;
+    /// let mut count = 0;
+    /// while let Some(click) = clicks.next(Duration::from_secs(30)).await {
+    ///     count += 1;
+    ///     click.update(format!("Clicked {} times", count)).await.ok();
+    /// }
+    /// # }
+    /// ```
+    pub fn collect_components<F>(&self, filter: F) -> ComponentCollector
+    where
+        F: Fn(&MessageComponentInteraction) -> bool + Send + Sync + 'static,
+    {
+        self.collector(filter, false)
+    }
+
+    fn collector<F>(&self, filter: F, once: bool) -> ComponentCollector
+    where
+        F: Fn(&MessageComponentInteraction) -> bool + Send + Sync + 'static,
+    {
+        let (id, receiver) =
+            self.collectors
+                .component(self.interaction.id, self.user().id, Box::new(filter), once);
+        ComponentCollector::new(
+            id,
+            receiver,
+            self.collectors.clone(),
+            self.serenity_context.clone(),
+            self.data.clone(),
+            self.custom_ids.clone(),
+        )
+    }
+
+    /// Gets the guild the command was used in, from the cache if possible, or over HTTP
+    pub async fn guild(&self) -> Option<PartialGuild> {
+        let guild_id = self.interaction.guild_id?;
+        if let Some(guild) = guild_id.to_guild_cached(&self.serenity_context.cache).await {
+            return Some(guild.into());
+        }
+        guild_id.to_partial_guild(self.http()).await.ok()
+    }
+
+    /// Gets the channel the command was used in, from the cache if possible, or over HTTP
+    pub async fn channel(&self) -> Option<Channel> {
+        self.interaction
+            .channel_id
+            .to_channel(&self.serenity_context)
+            .await
+            .ok()
+    }
+
+    /// Gets shared data registered with [`Framework::data`](crate::Framework::data)
+    /// # Panics
+    /// Panics if no data of type `T` was registered, see [`Context::try_data`]
+    pub fn data<T: Send + Sync + 'static>(&self) -> Arc<T> {
+        self.try_data::<T>().unwrap_or_else(|| {
+            panic!(
+                "No data of type {} registered in the framework",
+                std::any::type_name::<T>()
+            )
+        })
+    }
+
+    /// Gets shared data registered with [`Framework::data`](crate::Framework::data), if present
+    pub fn try_data<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.data.get::<T>()
+    }
+
+    pub(crate) fn data_map(&self) -> &DataMap {
+        &self.data
+    }
+
+    /// Downloads the contents of an attachment, ie one passed as a command argument
+    pub async fn download_attachment(&self, attachment: &Attachment) -> serenity::Result<Vec<u8>> {
+        attachment.download().await
+    }
+
     pub async fn reply<S: Into<String>>(&self, msg: S) {
         if self.responded {
             panic!("Already responded to the interaction")
@@ -58,3 +362,26 @@ impl Context {
             .ok();
     }
 }
+
+impl CacheHttp for Context {
+    fn http(&self) -> &Http {
+        self.serenity_context.http()
+    }
+
+    fn cache(&self) -> Option<&Arc<Cache>> {
+        Some(&self.serenity_context.cache)
+    }
+}
+
+/// Message reported to the user for an argument error, translated with its `ataraxy-*` Fluent
+/// message when the `i18n` feature is enabled and translations were registered
+#[cfg_attr(not(feature = "i18n"), allow(unused_variables))]
+pub(crate) fn error_message(data: &DataMap, locales: &[&str], error: &ArgumentError) -> String {
+    #[cfg(feature = "i18n")]
+    if let Some(translations) = data.get::<Translations>() {
+        if let Ok(translated) = translations.format_error(locales, error) {
+            return translated;
+        }
+    }
+    error.to_string()
+}
@@ -0,0 +1,625 @@
+//! Signed, versioned custom ids carrying a typed payload, so components keep their state
+//! across restarts without storing it anywhere
+//!
+//! Ids are formatted as `prefix:version:signature:payload`, where the payload is the value
+//! serialized as JSON with structs written as arrays (field names are left out to save space),
+//! and the signature is a truncated HMAC-SHA256 of the rest of the id. Because fields are
+//! positional, changing the fields of a payload should come with a new [`Payload::VERSION`].
+//! `#[serde(flatten)]` and `#[serde(skip_serializing_if = "...")]` are not supported.
+//!
+//! ```rust, no_run
+//! # use ataraxy::{component, ComponentContext, Payload};
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(Serialize, Deserialize)]
+//! struct Vote {
+//!     poll: u64,
+//!     choice: String,
+//! }
+//!
+//! impl Payload for Vote {
+//!     const PREFIX: &'static str = "vote";
+//!     const VERSION: u32 = 1;
+//! }
+//!
+//! #[component("vote:{id}")]
+//! async fn vote(ctx: ComponentContext, #[payload] vote: Vote) {
+//!     ctx.reply_ephemeral(format!("Voted for {}", vote.choice)).await.ok();
+//! }
+//! ```
+
+use ring::hmac;
+use serde::de::DeserializeOwned;
+use serde::ser::{self, Error as _, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::fmt::{Debug, Display, Formatter};
+
+/// Maximum length of a custom id allowed by Discord
+pub const MAX_CUSTOM_ID_LEN: usize = 100;
+
+/// Bytes of the HMAC kept in the id, 72 bits encoded as 12 characters
+const SIGNATURE_LEN: usize = 9;
+
+/// A value that can be stored in a custom id with a [`CustomIdCodec`]
+pub trait Payload: Serialize + DeserializeOwned {
+    /// Start of the custom id, matched by component handlers. Must not contain `:`
+    const PREFIX: &'static str;
+    /// Version of the payload, ids of other versions are rejected as outdated
+    const VERSION: u32;
+}
+
+/// Error encoding or decoding a custom id
+#[derive(Debug)]
+pub enum CustomIdError {
+    /// The encoded id would be longer than Discord allows
+    TooLong(usize),
+    /// The id does not start with the prefix of the payload, or is malformed
+    Mismatch,
+    /// The id was created for another version of the payload
+    Outdated { found: String, expected: u32 },
+    /// The signature does not match, the id was not created with this secret
+    Forged,
+    /// The payload could not be serialized or deserialized
+    Payload(serde_json::Error),
+    /// The [`Payload::PREFIX`] contains `:`, which separates the parts of the id
+    InvalidPrefix(&'static str),
+    /// No secret was registered with [`Framework::custom_id_secret`](crate::Framework::custom_id_secret)
+    NoSecret,
+}
+
+impl Display for CustomIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CustomIdError::TooLong(len) => write!(
+                f,
+                "Custom id is {} characters long, the maximum is {}",
+                len, MAX_CUSTOM_ID_LEN
+            ),
+            CustomIdError::Mismatch => write!(f, "Custom id does not match the payload type"),
+            CustomIdError::Outdated { found, expected } => write!(
+                f,
+                "Custom id has version {}, expected version {}",
+                found, expected
+            ),
+            CustomIdError::Forged => write!(f, "Custom id has an invalid signature"),
+            CustomIdError::Payload(e) => write!(f, "Invalid custom id payload: {}", e),
+            CustomIdError::InvalidPrefix(prefix) => {
+                write!(f, "Custom id prefix `{}` must not contain `:`", prefix)
+            }
+            CustomIdError::NoSecret => write!(
+                f,
+                "No custom id secret registered, see Framework::custom_id_secret"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CustomIdError {}
+
+/// Encodes and decodes signed custom ids, registered with
+/// [`Framework::custom_id_secret`](crate::Framework::custom_id_secret)
+pub struct CustomIdCodec {
+    key: hmac::Key,
+}
+
+impl CustomIdCodec {
+    /// Creates a codec signing ids with a secret, which must stay the same across restarts
+    /// for existing components to keep working
+    pub fn new<S: AsRef<[u8]>>(secret: S) -> Self {
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, secret.as_ref()),
+        }
+    }
+
+    fn sign(&self, message: &str) -> String {
+        let tag = hmac::sign(&self.key, message.as_bytes());
+        base64::encode_config(&tag.as_ref()[..SIGNATURE_LEN], base64::URL_SAFE_NO_PAD)
+    }
+
+    /// Encodes a payload into a custom id
+    pub fn encode<T: Payload>(&self, payload: &T) -> Result<String, CustomIdError> {
+        if T::PREFIX.contains(':') {
+            return Err(CustomIdError::InvalidPrefix(T::PREFIX));
+        }
+        let json = payload
+            .serialize(Compact)
+            .and_then(|value| serde_json::to_string(&value))
+            .map_err(CustomIdError::Payload)?;
+        let signature = self.sign(&format!("{}:{}:{}", T::PREFIX, T::VERSION, json));
+        let id = format!("{}:{}:{}:{}", T::PREFIX, T::VERSION, signature, json);
+        match id.chars().count() {
+            len if len > MAX_CUSTOM_ID_LEN => Err(CustomIdError::TooLong(len)),
+            _ => Ok(id),
+        }
+    }
+
+    /// Decodes a custom id created by [`CustomIdCodec::encode`], checking its prefix, version
+    /// and signature
+    pub fn decode<T: Payload>(&self, custom_id: &str) -> Result<T, CustomIdError> {
+        let mut parts = custom_id.splitn(4, ':');
+        let (prefix, version, signature, json) =
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(prefix), Some(version), Some(signature), Some(json)) => {
+                    (prefix, version, signature, json)
+                }
+                _ => return Err(CustomIdError::Mismatch),
+            };
+        if prefix != T::PREFIX {
+            return Err(CustomIdError::Mismatch);
+        }
+        if version != T::VERSION.to_string() {
+            return Err(CustomIdError::Outdated {
+                found: version.to_string(),
+                expected: T::VERSION,
+            });
+        }
+
+        let expected = self.sign(&format!("{}:{}:{}", prefix, version, json));
+        ring::constant_time::verify_slices_are_equal(expected.as_bytes(), signature.as_bytes())
+            .map_err(|_| CustomIdError::Forged)?;
+
+        serde_json::from_str(json).map_err(CustomIdError::Payload)
+    }
+}
+
+impl Debug for CustomIdCodec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CustomIdCodec")
+    }
+}
+
+/// Serializes values into JSON like `serde_json`, except that structs are written as arrays.
+/// Derived `Deserialize` implementations accept structs as arrays, so ids decode with `serde_json`
+struct Compact;
+
+type JsonResult<T> = std::result::Result<T, serde_json::Error>;
+
+impl Serializer for Compact {
+    type Ok = Value;
+    type Error = serde_json::Error;
+    type SerializeSeq = Seq;
+    type SerializeTuple = Seq;
+    type SerializeTupleStruct = Seq;
+    type SerializeTupleVariant = Variant;
+    type SerializeMap = Object;
+    type SerializeStruct = Seq;
+    type SerializeStructVariant = Variant;
+
+    fn serialize_bool(self, v: bool) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_char(self, v: char) -> JsonResult<Value> {
+        Ok(Value::from(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> JsonResult<Value> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_none(self) -> JsonResult<Value> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> JsonResult<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> JsonResult<Value> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> JsonResult<Value> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> JsonResult<Value> {
+        Ok(Value::from(variant))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> JsonResult<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> JsonResult<Value> {
+        let mut object = Map::new();
+        object.insert(variant.to_string(), value.serialize(Compact)?);
+        Ok(Value::Object(object))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> JsonResult<Seq> {
+        Ok(Seq(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> JsonResult<Seq> {
+        Ok(Seq(Vec::with_capacity(len)))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> JsonResult<Seq> {
+        Ok(Seq(Vec::with_capacity(len)))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> JsonResult<Variant> {
+        Ok(Variant(variant, Vec::with_capacity(len)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> JsonResult<Object> {
+        Ok(Object(Map::new(), None))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> JsonResult<Seq> {
+        Ok(Seq(Vec::with_capacity(len)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> JsonResult<Variant> {
+        Ok(Variant(variant, Vec::with_capacity(len)))
+    }
+}
+
+struct Seq(Vec<Value>);
+
+impl ser::SerializeSeq for Seq {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> JsonResult<()> {
+        self.0.push(value.serialize(Compact)?);
+        Ok(())
+    }
+
+    fn end(self) -> JsonResult<Value> {
+        Ok(Value::Array(self.0))
+    }
+}
+
+impl ser::SerializeTuple for Seq {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> JsonResult<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> JsonResult<Value> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for Seq {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> JsonResult<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> JsonResult<Value> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeStruct for Seq {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> JsonResult<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> JsonResult<Value> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Tuple and struct variants, written as `{"Variant": [...]}`
+struct Variant(&'static str, Vec<Value>);
+
+impl Variant {
+    fn finish(self) -> Value {
+        let mut object = Map::new();
+        object.insert(self.0.to_string(), Value::Array(self.1));
+        Value::Object(object)
+    }
+}
+
+impl ser::SerializeTupleVariant for Variant {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> JsonResult<()> {
+        self.1.push(value.serialize(Compact)?);
+        Ok(())
+    }
+
+    fn end(self) -> JsonResult<Value> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStructVariant for Variant {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> JsonResult<()> {
+        self.1.push(value.serialize(Compact)?);
+        Ok(())
+    }
+
+    fn end(self) -> JsonResult<Value> {
+        Ok(self.finish())
+    }
+}
+
+/// Maps, with the pending key between `serialize_key` and `serialize_value`
+struct Object(Map<String, Value>, Option<String>);
+
+impl ser::SerializeMap for Object {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> JsonResult<()> {
+        self.1 = Some(match key.serialize(Compact)? {
+            Value::String(key) => key,
+            Value::Number(key) => key.to_string(),
+            _ => {
+                return Err(serde_json::Error::custom(
+                    "Map keys must be strings or numbers",
+                ))
+            }
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> JsonResult<()> {
+        let key = self
+            .1
+            .take()
+            .ok_or_else(|| serde_json::Error::custom("Map value serialized before its key"))?;
+        self.0.insert(key, value.serialize(Compact)?);
+        Ok(())
+    }
+
+    fn end(self) -> JsonResult<Value> {
+        Ok(Value::Object(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Vote {
+        poll: u64,
+        choice: Option<String>,
+    }
+
+    impl Payload for Vote {
+        const PREFIX: &'static str = "vote";
+        const VERSION: u32 = 1;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct VoteV2 {
+        poll: u64,
+    }
+
+    impl Payload for VoteV2 {
+        const PREFIX: &'static str = "vote";
+        const VERSION: u32 = 2;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Action {
+        Close,
+        Page(u32),
+        Move(i8, i8),
+        Rename { name: String },
+        Tags(BTreeMap<String, u8>),
+    }
+
+    impl Payload for Action {
+        const PREFIX: &'static str = "action";
+        const VERSION: u32 = 1;
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Bad;
+
+    impl Payload for Bad {
+        const PREFIX: &'static str = "bad:prefix";
+        const VERSION: u32 = 1;
+    }
+
+    fn codec() -> CustomIdCodec {
+        CustomIdCodec::new("secret")
+    }
+
+    #[test]
+    fn round_trip() {
+        let vote = Vote {
+            poll: 42,
+            choice: Some("yes".to_string()),
+        };
+        let id = codec().encode(&vote).unwrap();
+        assert!(id.starts_with("vote:1:"));
+        assert!(id.ends_with(r#":[42,"yes"]"#));
+        assert_eq!(codec().decode::<Vote>(&id).unwrap(), vote);
+
+        let empty = Vote {
+            poll: 1,
+            choice: None,
+        };
+        let id = codec().encode(&empty).unwrap();
+        assert!(id.ends_with(":[1,null]"));
+        assert_eq!(codec().decode::<Vote>(&id).unwrap(), empty);
+    }
+
+    #[test]
+    fn enums() {
+        let actions = [
+            Action::Close,
+            Action::Page(3),
+            Action::Move(-1, 2),
+            Action::Rename {
+                name: "a:b".to_string(),
+            },
+            Action::Tags(
+                vec![("x".to_string(), 1), ("y".to_string(), 2)]
+                    .into_iter()
+                    .collect(),
+            ),
+        ];
+        for action in actions {
+            let id = codec().encode(&action).unwrap();
+            assert_eq!(codec().decode::<Action>(&id).unwrap(), action);
+        }
+        let id = codec()
+            .encode(&Action::Rename {
+                name: "a".to_string(),
+            })
+            .unwrap();
+        assert!(id.ends_with(r#":{"Rename":["a"]}"#));
+    }
+
+    #[test]
+    fn tampered() {
+        let id = codec()
+            .encode(&Vote {
+                poll: 42,
+                choice: None,
+            })
+            .unwrap();
+        let tampered = id.replace("[42,", "[43,");
+        assert!(matches!(
+            codec().decode::<Vote>(&tampered),
+            Err(CustomIdError::Forged)
+        ));
+        assert!(matches!(
+            CustomIdCodec::new("other").decode::<Vote>(&id),
+            Err(CustomIdError::Forged)
+        ));
+    }
+
+    #[test]
+    fn versions() {
+        let id = codec().encode(&VoteV2 { poll: 42 }).unwrap();
+        match codec().decode::<Vote>(&id) {
+            Err(CustomIdError::Outdated { found, expected }) => {
+                assert_eq!(found, "2");
+                assert_eq!(expected, 1);
+            }
+            other => panic!("Expected an outdated id, got {:?}", other),
+        }
+        assert!(matches!(
+            codec().decode::<Action>(&id),
+            Err(CustomIdError::Mismatch)
+        ));
+        assert!(matches!(
+            codec().decode::<Vote>("vote:1"),
+            Err(CustomIdError::Mismatch)
+        ));
+    }
+
+    #[test]
+    fn length() {
+        let vote = |len| Vote {
+            poll: 0,
+            choice: Some("x".repeat(len)),
+        };
+        let max = MAX_CUSTOM_ID_LEN - codec().encode(&vote(0)).unwrap().len();
+        assert_eq!(codec().encode(&vote(max)).unwrap().len(), MAX_CUSTOM_ID_LEN);
+        assert!(matches!(
+            codec().encode(&vote(max + 1)),
+            Err(CustomIdError::TooLong(len)) if len == MAX_CUSTOM_ID_LEN + 1
+        ));
+    }
+
+    #[test]
+    fn invalid_prefix() {
+        assert!(matches!(
+            codec().encode(&Bad),
+            Err(CustomIdError::InvalidPrefix("bad:prefix"))
+        ));
+    }
+}
@@ -0,0 +1,77 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// Type map holding shared application data, keyed by type
+/// Values are stored behind an [`Arc`] so read-only data can be shared without locks
+#[derive(Clone, Default)]
+pub struct DataMap(HashMap<TypeId, Arc<dyn Any + Send + Sync>>);
+
+impl DataMap {
+    pub fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    /// Inserts a value, replacing any previous value of the same type
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.0.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Gets the value of type `T`, if one was inserted
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.0
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|value| value.downcast::<T>().ok())
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.0.contains_key(&TypeId::of::<T>())
+    }
+}
+
+impl Debug for DataMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DataMap({} entries)", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Pool(u32);
+    struct Config;
+
+    #[test]
+    fn returns_the_same_value() {
+        let mut data = DataMap::new();
+        data.insert(Pool(5));
+
+        let first = data.get::<Pool>().unwrap();
+        let second = data.get::<Pool>().unwrap();
+        assert_eq!(first.0, 5);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(Arc::ptr_eq(&first, &data.clone().get::<Pool>().unwrap()));
+    }
+
+    #[test]
+    fn missing_types_return_none() {
+        let mut data = DataMap::new();
+        assert!(data.get::<Pool>().is_none());
+
+        data.insert(Pool(5));
+        assert!(data.get::<Config>().is_none());
+        assert!(!data.contains::<Config>());
+        assert!(data.contains::<Pool>());
+    }
+
+    #[test]
+    fn replaces_values_of_the_same_type() {
+        let mut data = DataMap::new();
+        data.insert(Pool(5));
+        data.insert(Pool(6));
+        assert_eq!(data.get::<Pool>().unwrap().0, 6);
+    }
+}
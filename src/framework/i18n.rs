@@ -0,0 +1,348 @@
+//! Response localization with [Fluent](https://projectfluent.org), enabled with the `i18n` feature
+//!
+//! Translations are registered with [`Framework::translations`](crate::Framework::translations)
+//! and resolved with [`Context::t`](crate::Context::t), trying the locale of the invoking user,
+//! then the locale of the guild, then the default locale. A locale such as `es-ES` falls back to
+//! a bundle for its language (`es`) when there is no bundle for the exact locale.
+//!
+//! Errors reported by the framework can be translated with the following messages, which all
+//! receive the built-in English text as `$message`, along with the listed arguments:
+//!
+//! | Message                                | Arguments                 |
+//! |----------------------------------------|---------------------------|
+//! | `ataraxy-unknown-type`                 |                           |
+//! | `ataraxy-incorrect-type`               |                           |
+//! | `ataraxy-not-provided`                 |                           |
+//! | `ataraxy-preprocessing`                |                           |
+//! | `ataraxy-extraction`                   |                           |
+//! | `ataraxy-not-in-guild`                 |                           |
+//! | `ataraxy-invalid-attachment`           |                           |
+//! | `ataraxy-validation-failed`            | `$argument`, `$reason`    |
+//! | `ataraxy-invalid-id`                   | `$value`                  |
+//! | `ataraxy-invalid-message-link`         |                           |
+//! | `ataraxy-missing-permissions`          |                           |
+//! | `ataraxy-invalid-value`                |                           |
+//! | `ataraxy-zero`                         |                           |
+//! | `ataraxy-time-empty`                   |                           |
+//! | `ataraxy-time-invalid-number`          | `$value`                  |
+//! | `ataraxy-time-missing-unit`            | `$value`                  |
+//! | `ataraxy-time-unknown-unit`            | `$unit`                   |
+//! | `ataraxy-time-invalid-date`            | `$value`                  |
+//! | `ataraxy-time-unknown-zone`            | `$value`                  |
+//! | `ataraxy-time-too-small`               | `$bound`                  |
+//! | `ataraxy-time-too-large`               | `$bound`                  |
+//! | `ataraxy-time-out-of-range`            | `$value`                  |
+//! | `ataraxy-time-invalid-bound`           | `$bound`                  |
+//!
+//! ```ftl
+//! ataraxy-time-unknown-unit = Unbekannte Einheit `{ $unit }`, erwartet s, m, h, d oder w
+//! ataraxy-time-too-small = Muss mindestens { $bound } sein
+//! ```
+//!
+//! A framework error whose message is missing or fails to format is reported in English.
+
+use crate::framework::command::argument::ArgumentError;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::path::Path;
+use std::sync::Arc;
+use unic_langid::LanguageIdentifier;
+
+/// Error loading Fluent translations
+#[derive(Debug)]
+pub enum TranslationError {
+    /// A translation file could not be read
+    Io(std::io::Error),
+    /// A file name or locale is not a valid language identifier
+    InvalidLocale(String),
+    /// A Fluent resource has syntax errors
+    Parse { locale: String, errors: Vec<String> },
+    /// A message is defined more than once for a locale
+    Duplicate { locale: String, errors: Vec<String> },
+    /// No bundle has the message, nor a value for it
+    MissingMessage(String),
+    /// A message failed to format, ie because it references an argument that was not given
+    Format {
+        locale: String,
+        key: String,
+        errors: Vec<String>,
+    },
+}
+
+impl Display for TranslationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranslationError::Io(e) => write!(f, "Could not read translations: {}", e),
+            TranslationError::InvalidLocale(locale) => write!(f, "Invalid locale `{}`", locale),
+            TranslationError::Parse { locale, errors } => write!(
+                f,
+                "Invalid Fluent syntax in `{}` translations: {}",
+                locale,
+                errors.join(", ")
+            ),
+            TranslationError::Duplicate { locale, errors } => write!(
+                f,
+                "Duplicate messages in `{}` translations: {}",
+                locale,
+                errors.join(", ")
+            ),
+            TranslationError::MissingMessage(key) => write!(f, "No translation for `{}`", key),
+            TranslationError::Format {
+                locale,
+                key,
+                errors,
+            } => write!(
+                f,
+                "Could not format `{}` in `{}`: {}",
+                key,
+                locale,
+                errors.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TranslationError {}
+
+impl From<std::io::Error> for TranslationError {
+    fn from(e: std::io::Error) -> Self {
+        TranslationError::Io(e)
+    }
+}
+
+/// Fluent bundles keyed by Discord locale (ie `de` or `es-ES`)
+pub struct Translations {
+    bundles: HashMap<String, FluentBundle<Arc<FluentResource>>>,
+    /// Resources of each bundle, to rebuild it when a resource is added
+    resources: HashMap<String, Vec<Arc<FluentResource>>>,
+    default_locale: String,
+}
+
+impl Translations {
+    /// Creates empty translations, falling back to `default_locale` when a message is not
+    /// translated in the locale of the user or guild
+    pub fn new<S: Into<String>>(default_locale: S) -> Self {
+        Self {
+            bundles: HashMap::new(),
+            resources: HashMap::new(),
+            default_locale: default_locale.into(),
+        }
+    }
+
+    /// Loads every `<locale>.ftl` file of a directory, ie `locales/en-US.ftl` and `locales/de.ftl`
+    pub fn load_dir<S: Into<String>, P: AsRef<Path>>(
+        default_locale: S,
+        dir: P,
+    ) -> Result<Self, TranslationError> {
+        let mut translations = Self::new(default_locale);
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("ftl") {
+                continue;
+            }
+            let locale = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .ok_or_else(|| TranslationError::InvalidLocale(path.display().to_string()))?
+                .to_string();
+            let source = std::fs::read_to_string(&path)?;
+            translations.add_resource(&locale, source)?;
+        }
+        Ok(translations)
+    }
+
+    /// Adds Fluent source for a locale, creating its bundle if needed.
+    /// The bundle is left unchanged if the source fails to parse or redefines a message
+    pub fn add_resource<S: Into<String>>(
+        &mut self,
+        locale: &str,
+        source: S,
+    ) -> Result<(), TranslationError> {
+        let resource = FluentResource::try_new(source.into()).map_err(|(_, errors)| {
+            TranslationError::Parse {
+                locale: locale.to_string(),
+                errors: errors.iter().map(|e| format!("{:?}", e)).collect(),
+            }
+        })?;
+
+        let language: LanguageIdentifier = locale
+            .parse()
+            .map_err(|_| TranslationError::InvalidLocale(locale.to_string()))?;
+        // Fluent adds the messages of a resource until it finds a duplicate, so the bundle is
+        // rebuilt and only replaced once every resource was added
+        let mut bundle = FluentBundle::new_concurrent(vec![language]);
+        // Isolation marks around arguments show up as garbage in some Discord clients
+        bundle.set_use_isolating(false);
+        let mut resources = self.resources.get(locale).cloned().unwrap_or_default();
+        resources.push(Arc::new(resource));
+        for resource in &resources {
+            bundle
+                .add_resource(Arc::clone(resource))
+                .map_err(|errors| TranslationError::Duplicate {
+                    locale: locale.to_string(),
+                    errors: errors.iter().map(|e| format!("{:?}", e)).collect(),
+                })?;
+        }
+
+        self.bundles.insert(locale.to_string(), bundle);
+        self.resources.insert(locale.to_string(), resources);
+        Ok(())
+    }
+
+    pub fn default_locale(&self) -> &str {
+        &self.default_locale
+    }
+
+    /// Gets the bundle of a locale, or of its language
+    fn bundle(&self, locale: &str) -> Option<&FluentBundle<Arc<FluentResource>>> {
+        self.bundles.get(locale).or_else(|| {
+            let language = locale.split('-').next()?;
+            self.bundles.get(language)
+        })
+    }
+
+    /// Formats a message in the first of `locales` that has it, then in the default locale
+    pub fn format(
+        &self,
+        locales: &[&str],
+        key: &str,
+        args: Option<&FluentArgs>,
+    ) -> Result<String, TranslationError> {
+        let (locale, bundle, pattern) = locales
+            .iter()
+            .copied()
+            .chain(std::iter::once(self.default_locale.as_str()))
+            .find_map(|locale| {
+                let bundle = self.bundle(locale)?;
+                let pattern = bundle.get_message(key)?.value()?;
+                Some((locale, bundle, pattern))
+            })
+            .ok_or_else(|| TranslationError::MissingMessage(key.to_string()))?;
+
+        let mut errors = Vec::new();
+        let text = bundle
+            .format_pattern(pattern, args, &mut errors)
+            .into_owned();
+        if errors.is_empty() {
+            Ok(text)
+        } else {
+            Err(TranslationError::Format {
+                locale: locale.to_string(),
+                key: key.to_string(),
+                errors: errors.iter().map(|e| format!("{:?}", e)).collect(),
+            })
+        }
+    }
+
+    /// Formats a framework error with its `ataraxy-*` message, if one was translated
+    pub(crate) fn format_error(
+        &self,
+        locales: &[&str],
+        error: &ArgumentError,
+    ) -> Result<String, TranslationError> {
+        let mut args = FluentArgs::new();
+        args.set("message", error.to_string());
+        for (name, value) in error.message_args() {
+            args.set(name, value);
+        }
+        self.format(locales, error.message_id(), Some(&args))
+    }
+}
+
+impl Debug for Translations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut locales: Vec<&String> = self.bundles.keys().collect();
+        locales.sort();
+        f.debug_struct("Translations")
+            .field("locales", &locales)
+            .field("default_locale", &self.default_locale)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translations() -> Translations {
+        let mut translations = Translations::new("en-US");
+        translations
+            .add_resource("en-US", "hello = Hello\ngreet = Hello { $name }\n")
+            .unwrap();
+        translations.add_resource("es", "hello = Hola\n").unwrap();
+        translations
+    }
+
+    #[test]
+    fn falls_back_to_language() {
+        let translations = translations();
+        assert_eq!(
+            translations.format(&["es-ES"], "hello", None).unwrap(),
+            "Hola"
+        );
+        assert_eq!(
+            translations.format(&["de"], "hello", None).unwrap(),
+            "Hello"
+        );
+
+        let mut args = FluentArgs::new();
+        args.set("name", "Ana");
+        assert_eq!(
+            translations
+                .format(&["es-ES"], "greet", Some(&args))
+                .unwrap(),
+            "Hello Ana"
+        );
+    }
+
+    #[test]
+    fn reports_missing_messages() {
+        let translations = translations();
+        assert!(matches!(
+            translations.format(&["es"], "goodbye", None),
+            Err(TranslationError::MissingMessage(key)) if key == "goodbye"
+        ));
+    }
+
+    #[test]
+    fn reports_missing_arguments() {
+        let translations = translations();
+        match translations.format(&["es"], "greet", None) {
+            Err(TranslationError::Format {
+                locale,
+                key,
+                errors,
+            }) => {
+                assert_eq!(locale, "en-US");
+                assert_eq!(key, "greet");
+                assert_eq!(errors.len(), 1);
+            }
+            result => panic!("Expected a format error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn duplicates_leave_the_bundle_unchanged() {
+        let mut translations = translations();
+        assert!(matches!(
+            translations.add_resource("es", "goodbye = Adiós\nhello = Buenas\n"),
+            Err(TranslationError::Duplicate { .. })
+        ));
+        assert_eq!(translations.format(&["es"], "hello", None).unwrap(), "Hola");
+        assert!(matches!(
+            translations.format(&["es"], "goodbye", None),
+            Err(TranslationError::MissingMessage(_))
+        ));
+
+        translations
+            .add_resource("es", "goodbye = Adiós\n")
+            .unwrap();
+        assert_eq!(
+            translations.format(&["es"], "goodbye", None).unwrap(),
+            "Adiós"
+        );
+        assert_eq!(translations.format(&["es"], "hello", None).unwrap(), "Hola");
+    }
+}
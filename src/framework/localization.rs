@@ -0,0 +1,358 @@
+use crate::framework::command::argument::CommandArgumentSignature;
+use crate::framework::{Command, SubCommand, ValidCommand};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Translations of a name or description, keyed by Discord locale (ie `de` or `es-ES`)
+pub type LocalizedText = HashMap<String, String>;
+
+/// Translation bundle for command names and descriptions, registered with
+/// [`Framework::localizations`](crate::Framework::localizations).
+///
+/// Keys are the path of a command followed by the path of an option and `name` or `description`:
+/// `ban.name`, `ban.description`, `ban.user.name`, `settings.prefix.description` for a subcommand,
+/// or `region.region.choices.Europe` for the name of a choice. Translations given in the
+/// [`command`](crate::command) macro take precedence over the bundle
+#[derive(Debug, Clone, Default)]
+pub struct Localizations(HashMap<String, LocalizedText>);
+
+impl Localizations {
+    pub fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    /// Adds a translation, replacing any previous translation of the key in this locale
+    pub fn insert<L: Into<String>, K: Into<String>, T: Into<String>>(
+        &mut self,
+        locale: L,
+        key: K,
+        text: T,
+    ) {
+        self.0
+            .entry(key.into())
+            .or_insert_with(HashMap::new)
+            .insert(locale.into(), text.into());
+    }
+
+    /// Adds the translations of a locale from a flat JSON object: `{"ban.name": "bannen"}`
+    pub fn load_json<L: Into<String>>(&mut self, locale: L, json: &str) -> serde_json::Result<()> {
+        let locale = locale.into();
+        let entries: HashMap<String, String> = serde_json::from_str(json)?;
+        for (key, text) in entries {
+            self.insert(locale.clone(), key, text);
+        }
+        Ok(())
+    }
+
+    /// Gets the translations of a key
+    pub fn get(&self, key: &str) -> Option<&LocalizedText> {
+        self.0.get(key)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Adds the translations of `key` to `target` for the locales it does not have yet
+    fn fill(&self, key: &str, target: &mut LocalizedText) {
+        if let Some(translations) = self.get(key) {
+            for (locale, text) in translations {
+                target.entry(locale.clone()).or_insert_with(|| text.clone());
+            }
+        }
+    }
+
+    /// Fills the translations of a command and its options from the bundle
+    pub(crate) fn apply(&self, command: &mut ValidCommand) {
+        match command {
+            ValidCommand::Command(command) => self.apply_command(&command.name.clone(), command),
+            ValidCommand::SubCommands(subcommands) => {
+                let path = subcommands.name.clone();
+                self.fill(
+                    &format!("{}.name", path),
+                    &mut subcommands.name_localizations,
+                );
+                self.fill(
+                    &format!("{}.description", path),
+                    &mut subcommands.description_localizations,
+                );
+                for subcommand in subcommands.subcommands.values_mut() {
+                    match subcommand {
+                        SubCommand::SubCommand(command) => {
+                            self.apply_command(&format!("{}.{}", path, command.name), command)
+                        }
+                        SubCommand::SubCommandGroup(group) => {
+                            let path = format!("{}.{}", path, group.name);
+                            self.fill(&format!("{}.name", path), &mut group.name_localizations);
+                            self.fill(
+                                &format!("{}.description", path),
+                                &mut group.description_localizations,
+                            );
+                            for command in group.subcommands.values_mut() {
+                                self.apply_command(&format!("{}.{}", path, command.name), command);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn apply_command(&self, path: &str, command: &mut Command) {
+        self.fill(&format!("{}.name", path), &mut command.name_localizations);
+        self.fill(
+            &format!("{}.description", path),
+            &mut command.description_localizations,
+        );
+        for argument in &mut command.arguments.arguments {
+            self.apply_argument(&format!("{}.{}", path, argument.name), argument);
+        }
+    }
+
+    fn apply_argument(&self, path: &str, argument: &mut CommandArgumentSignature) {
+        self.fill(&format!("{}.name", path), &mut argument.name_localizations);
+        self.fill(
+            &format!("{}.description", path),
+            &mut argument.description_localizations,
+        );
+        if let Some(choices) = &argument.options.choices {
+            for (name, _) in choices {
+                let key = format!("{}.choices.{}", path, name);
+                if self.get(&key).is_some() {
+                    let target = argument
+                        .choice_localizations
+                        .entry(name.clone())
+                        .or_default();
+                    self.fill(&key, target);
+                }
+            }
+        }
+    }
+}
+
+/// Inserts the `name_localizations` and `description_localizations` fields into a builder
+pub(crate) fn insert_localizations(
+    builder: &mut HashMap<&'static str, Value>,
+    name: &LocalizedText,
+    description: &LocalizedText,
+) {
+    if !name.is_empty() {
+        builder.insert("name_localizations", serde_json::json!(name));
+    }
+    if !description.is_empty() {
+        builder.insert("description_localizations", serde_json::json!(description));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::command::argument::{
+        ArgumentError, ArgumentList, ChoiceValue, CommandArgumentOptions, CommandArgumentType,
+        CommandArgumentValueType, CommandSignature,
+    };
+    use crate::framework::command::CommandHandler;
+    use crate::framework::{CommandGroup, Context, SubCommands};
+    use std::future::Future;
+    use std::pin::Pin;
+
+    fn handler(
+        _: Context,
+        _: &ArgumentList,
+    ) -> Pin<Box<dyn Future<Output = Result<(), ArgumentError>> + Send>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn argument(
+        name: &str,
+        choices: Option<Vec<(String, ChoiceValue)>>,
+    ) -> CommandArgumentSignature {
+        CommandArgumentSignature {
+            name: name.to_string(),
+            description: "An option".to_string(),
+            name_localizations: HashMap::new(),
+            description_localizations: HashMap::new(),
+            choice_localizations: HashMap::new(),
+            argument: CommandArgumentType {
+                optional: false,
+                value_type: CommandArgumentValueType::String,
+            },
+            options: CommandArgumentOptions {
+                min: None,
+                max: None,
+                min_text: None,
+                max_text: None,
+                min_len: None,
+                max_len: None,
+                channel_type: None,
+                content_types: None,
+                max_size: None,
+                regex: None,
+                choices,
+            },
+        }
+    }
+
+    fn command(name: &str, arguments: Vec<CommandArgumentSignature>) -> Command {
+        Command {
+            name: name.to_string(),
+            description: "A command".to_string(),
+            name_localizations: HashMap::new(),
+            description_localizations: HashMap::new(),
+            guilds: None,
+            arguments: CommandSignature {
+                context: true,
+                arguments,
+            },
+            action: CommandHandler(handler),
+        }
+    }
+
+    fn prefix() -> Command {
+        let choices = vec![
+            ("Short".to_string(), ChoiceValue::String("!".to_string())),
+            ("Long".to_string(), ChoiceValue::String("!!".to_string())),
+        ];
+        command("prefix", vec![argument("value", Some(choices))])
+    }
+
+    fn reset() -> Command {
+        let mut reset = command("reset", vec![argument("key", None)]);
+        reset
+            .name_localizations
+            .insert("de".into(), "zurücksetzen".into());
+        reset
+    }
+
+    fn settings() -> ValidCommand {
+        ValidCommand::SubCommands(
+            SubCommands::new("settings", "Settings")
+                .command(prefix)
+                .group(CommandGroup::new("admin", "Admin settings", vec![reset])),
+        )
+    }
+
+    fn subcommand<'a>(command: &'a ValidCommand, name: &str) -> &'a SubCommand {
+        match command {
+            ValidCommand::SubCommands(subcommands) => &subcommands.subcommands[name],
+            ValidCommand::Command(_) => panic!("Expected subcommands"),
+        }
+    }
+
+    #[test]
+    fn applies_keys_of_subcommands_and_groups() {
+        let mut localizations = Localizations::new();
+        localizations.insert("de", "settings.name", "einstellungen");
+        localizations.insert("de", "settings.prefix.description", "Ändert das Präfix");
+        localizations.insert("de", "settings.prefix.value.name", "wert");
+        localizations.insert("de", "settings.admin.name", "verwaltung");
+        localizations.insert("fr", "settings.admin.reset.key.description", "La clé");
+
+        let mut command = settings();
+        localizations.apply(&mut command);
+
+        match &command {
+            ValidCommand::SubCommands(subcommands) => {
+                assert_eq!(subcommands.name_localizations["de"], "einstellungen");
+                assert!(subcommands.description_localizations.is_empty());
+            }
+            ValidCommand::Command(_) => panic!("Expected subcommands"),
+        }
+        match subcommand(&command, "prefix") {
+            SubCommand::SubCommand(prefix) => {
+                assert_eq!(prefix.description_localizations["de"], "Ändert das Präfix");
+                assert_eq!(
+                    prefix.arguments.arguments[0].name_localizations["de"],
+                    "wert"
+                );
+            }
+            SubCommand::SubCommandGroup(_) => panic!("Expected a subcommand"),
+        }
+        match subcommand(&command, "admin") {
+            SubCommand::SubCommandGroup(group) => {
+                assert_eq!(group.name_localizations["de"], "verwaltung");
+                let key = &group.subcommands["reset"].arguments.arguments[0];
+                assert_eq!(key.description_localizations["fr"], "La clé");
+                assert!(key.name_localizations.is_empty());
+            }
+            SubCommand::SubCommand(_) => panic!("Expected a group"),
+        }
+    }
+
+    #[test]
+    fn applies_choices() {
+        let mut localizations = Localizations::new();
+        localizations.insert("de", "settings.prefix.value.choices.Short", "Kurz");
+        localizations.insert("fr", "settings.prefix.value.choices.Short", "Court");
+
+        let mut command = settings();
+        localizations.apply(&mut command);
+
+        match subcommand(&command, "prefix") {
+            SubCommand::SubCommand(prefix) => {
+                let choices = &prefix.arguments.arguments[0].choice_localizations;
+                assert_eq!(choices["Short"]["de"], "Kurz");
+                assert_eq!(choices["Short"]["fr"], "Court");
+                assert!(!choices.contains_key("Long"));
+            }
+            SubCommand::SubCommandGroup(_) => panic!("Expected a subcommand"),
+        }
+    }
+
+    #[test]
+    fn macro_translations_take_precedence() {
+        let mut localizations = Localizations::new();
+        localizations.insert("de", "settings.admin.reset.name", "löschen");
+        localizations.insert("fr", "settings.admin.reset.name", "réinitialiser");
+
+        let mut command = settings();
+        localizations.apply(&mut command);
+
+        match subcommand(&command, "admin") {
+            SubCommand::SubCommandGroup(group) => {
+                let reset = &group.subcommands["reset"];
+                assert_eq!(reset.name_localizations["de"], "zurücksetzen");
+                assert_eq!(reset.name_localizations["fr"], "réinitialiser");
+            }
+            SubCommand::SubCommand(_) => panic!("Expected a group"),
+        }
+    }
+
+    #[test]
+    fn applies_keys_of_commands() {
+        let mut localizations = Localizations::new();
+        localizations.insert("de", "prefix.name", "präfix");
+        localizations.insert("de", "prefix.value.description", "Das neue Präfix");
+
+        let mut command = ValidCommand::Command(prefix());
+        localizations.apply(&mut command);
+
+        match &command {
+            ValidCommand::Command(prefix) => {
+                assert_eq!(prefix.name_localizations["de"], "präfix");
+                assert_eq!(
+                    prefix.arguments.arguments[0].description_localizations["de"],
+                    "Das neue Präfix"
+                );
+            }
+            ValidCommand::SubCommands(_) => panic!("Expected a command"),
+        }
+    }
+
+    #[test]
+    fn loads_json() {
+        let mut localizations = Localizations::new();
+        localizations
+            .load_json("de", r#"{"ban.name": "bannen", "ban.user.name": "nutzer"}"#)
+            .unwrap();
+        assert_eq!(localizations.get("ban.name").unwrap()["de"], "bannen");
+        assert_eq!(localizations.get("ban.user.name").unwrap()["de"], "nutzer");
+
+        assert!(localizations.load_json("fr", r#"{"ban.name": 5}"#).is_err());
+        assert!(localizations
+            .load_json("fr", r#"{"ban": {"name": "bannir"}}"#)
+            .is_err());
+        assert!(localizations.load_json("fr", r#"["bannir"]"#).is_err());
+        assert!(localizations.get("ban.name").unwrap().get("fr").is_none());
+    }
+}
@@ -12,16 +12,38 @@ use serenity::{
 
 use serenity::http::CacheHttp;
 use serenity::model::prelude::application_command::{
-    ApplicationCommand, ApplicationCommandOptionType,
+    ApplicationCommand, ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    ApplicationCommandOptionType,
 };
 use std::collections::HashMap;
+use std::sync::Arc;
 
+mod collector;
 pub mod command;
+pub mod component;
 mod context;
-
-use crate::framework::command::argument::{ArgumentList, CommandArgument, CommandArgumentValue};
+pub mod custom_id;
+mod data;
+#[cfg(feature = "i18n")]
+mod i18n;
+mod localization;
+pub mod modal;
+
+use crate::framework::collector::{Collected, Collectors};
+use crate::framework::command::argument::{
+    ArgumentError, ArgumentList, CommandArgument, CommandArgumentValue, MAX_OPTIONS,
+};
+pub use collector::ComponentCollector;
 pub use command::Command;
+pub use component::{Component, ComponentContext, IntoComponent};
 pub use context::Context;
+pub use custom_id::{CustomIdCodec, CustomIdError, Payload};
+pub use data::DataMap;
+#[cfg(feature = "i18n")]
+pub use i18n::{TranslationError, Translations};
+use localization::insert_localizations;
+pub use localization::{Localizations, LocalizedText};
+pub use modal::{Modal, ModalError, ModalSubmit};
 
 /// Defines how slash commands should be created and/or merged with existing ones
 #[derive(Clone, Debug)]
@@ -37,6 +59,12 @@ pub enum CommandMergeMethod {
 pub struct Framework {
     commands: HashMap<String, Vec<ValidCommand>>,
     command_merging: CommandMergeMethod,
+    data: Arc<DataMap>,
+    localizations: Localizations,
+    components: Vec<Component>,
+    /// Modals and components awaited by running commands
+    collectors: Arc<Collectors>,
+    custom_ids: Option<Arc<CustomIdCodec>>,
 }
 
 pub trait IntoValidCommand {
@@ -66,6 +94,11 @@ impl Framework {
         Self {
             commands: HashMap::new(),
             command_merging: CommandMergeMethod::Set,
+            data: Arc::new(DataMap::new()),
+            localizations: Localizations::new(),
+            components: Vec::new(),
+            collectors: Arc::new(Collectors::default()),
+            custom_ids: None,
         }
     }
 
@@ -75,8 +108,12 @@ impl Framework {
     // What is weird is that SubCommand::command() doesn't freak aut about
     // the function signature change for some reason ¯\_(ツ)_/¯
     #[command_ide_arg_support]
+    /// # Panics
+    /// Panics if the command or one of its subcommands has more than [`MAX_OPTIONS`] options,
+    /// including the ones of flattened arguments, since Discord would reject it
     pub fn command<T: Any>(mut self, cmd: T) -> Self {
         let command = cmd.into_command();
+        check_option_count(&command);
         self.commands
             .entry(command.name().clone())
             .or_insert_with(Vec::new)
@@ -88,6 +125,54 @@ impl Framework {
         self.command_merging = method;
         self
     }
+
+    /// Registers shared data, accessible from commands through [`Context::data`]
+    /// Registering a value of the same type twice replaces the previous value
+    pub fn data<T: Send + Sync + 'static>(mut self, value: T) -> Self {
+        Arc::make_mut(&mut self.data).insert(value);
+        self
+    }
+
+    /// Sets the translations of command names and descriptions, see [`Localizations`]
+    pub fn localizations(mut self, localizations: Localizations) -> Self {
+        self.localizations = localizations;
+        self
+    }
+
+    /// Adds a handler for buttons and select menus, see [`component!`](crate::component).
+    /// Handlers are tried in the order they were added, so more specific patterns go first
+    pub fn component<T: IntoComponent>(mut self, component: T) -> Self {
+        self.components.push(component.into_component());
+        self
+    }
+
+    /// Sets the secret used to sign custom ids carrying a [`Payload`], see [`custom_id`]
+    pub fn custom_id_secret<S: AsRef<[u8]>>(mut self, secret: S) -> Self {
+        self.custom_ids = Some(Arc::new(CustomIdCodec::new(secret)));
+        self
+    }
+
+    /// Sets the Fluent translations used by [`Context::t`] and for the framework's error messages
+    #[cfg(feature = "i18n")]
+    pub fn translations(self, translations: Translations) -> Self {
+        self.data(translations)
+    }
+}
+
+impl Framework {
+    fn context(
+        &self,
+        ctx: &SerenityContext,
+        interaction: &ApplicationCommandInteraction,
+    ) -> Context {
+        Context::from_framework(
+            ctx,
+            interaction,
+            self.data.clone(),
+            self.collectors.clone(),
+            self.custom_ids.clone(),
+        )
+    }
 }
 
 impl Default for Framework {
@@ -101,6 +186,8 @@ impl Default for Framework {
 pub struct SubCommands {
     pub name: String,
     pub description: String,
+    pub name_localizations: LocalizedText,
+    pub description_localizations: LocalizedText,
     pub guilds: Option<Vec<u64>>,
     pub subcommands: HashMap<String, SubCommand>,
 }
@@ -111,11 +198,26 @@ impl SubCommands {
         Self {
             name: name.into(),
             description: description.into(),
+            name_localizations: HashMap::new(),
+            description_localizations: HashMap::new(),
             guilds: None,
             subcommands: HashMap::new(),
         }
     }
 
+    /// Adds a translation of the name for a Discord locale (ie `de` or `es-ES`)
+    pub fn name_localized<S: Into<String>>(mut self, locale: S, name: S) -> Self {
+        self.name_localizations.insert(locale.into(), name.into());
+        self
+    }
+
+    /// Adds a translation of the description for a Discord locale
+    pub fn description_localized<S: Into<String>>(mut self, locale: S, description: S) -> Self {
+        self.description_localizations
+            .insert(locale.into(), description.into());
+        self
+    }
+
     /// Add a subcommand to the command
     pub fn command(mut self, cmd: fn() -> Command) -> Self {
         let cmd = cmd();
@@ -144,6 +246,8 @@ impl SubCommands {
 pub struct CommandGroup {
     pub name: String,
     pub description: String,
+    pub name_localizations: LocalizedText,
+    pub description_localizations: LocalizedText,
     pub subcommands: HashMap<String, Command>,
 }
 impl CommandGroup {
@@ -155,6 +259,8 @@ impl CommandGroup {
         Self {
             name: name.into(),
             description: description.into(),
+            name_localizations: HashMap::new(),
+            description_localizations: HashMap::new(),
             subcommands: commands
                 .into_iter()
                 .map(|c| {
@@ -164,6 +270,19 @@ impl CommandGroup {
                 .collect(),
         }
     }
+
+    /// Adds a translation of the name for a Discord locale (ie `de` or `es-ES`)
+    pub fn name_localized<S: Into<String>>(mut self, locale: S, name: S) -> Self {
+        self.name_localizations.insert(locale.into(), name.into());
+        self
+    }
+
+    /// Adds a translation of the description for a Discord locale
+    pub fn description_localized<S: Into<String>>(mut self, locale: S, description: S) -> Self {
+        self.description_localizations
+            .insert(locale.into(), description.into());
+        self
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -202,10 +321,54 @@ impl ValidCommand {
     }
 }
 
-fn create_command(command: &ValidCommand, cmds: &mut CreateApplicationCommands) {
+fn check_option_count(command: &ValidCommand) {
+    let check = |path: &str, command: &Command| {
+        let count = command.arguments.arguments.len();
+        assert!(
+            count <= MAX_OPTIONS,
+            "Command `{}` has {} options, Discord accepts at most {}",
+            path,
+            count,
+            MAX_OPTIONS
+        );
+    };
     match command {
+        ValidCommand::Command(command) => check(&command.name, command),
+        ValidCommand::SubCommands(command) => {
+            for subcommand in command.subcommands.values() {
+                match subcommand {
+                    SubCommand::SubCommand(sub) => {
+                        check(&format!("{} {}", command.name, sub.name), sub)
+                    }
+                    SubCommand::SubCommandGroup(group) => {
+                        for sub in group.subcommands.values() {
+                            check(
+                                &format!("{} {} {}", command.name, group.name, sub.name),
+                                sub,
+                            )
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn create_command(
+    command: &ValidCommand,
+    localizations: &Localizations,
+    cmds: &mut CreateApplicationCommands,
+) {
+    let mut command = command.clone();
+    localizations.apply(&mut command);
+    match &command {
         ValidCommand::Command(command) => {
             cmds.create_application_command(|cmd| {
+                insert_localizations(
+                    &mut cmd.0,
+                    &command.name_localizations,
+                    &command.description_localizations,
+                );
                 cmd.name(&command.name)
                     .description(&command.description)
                     .kind(ApplicationCommandType::ChatInput)
@@ -221,6 +384,11 @@ fn create_command(command: &ValidCommand, cmds: &mut CreateApplicationCommands)
         }
         ValidCommand::SubCommands(subcommands) => {
             cmds.create_application_command(|cmd| {
+                insert_localizations(
+                    &mut cmd.0,
+                    &subcommands.name_localizations,
+                    &subcommands.description_localizations,
+                );
                 cmd.name(&subcommands.name)
                     .description(&subcommands.description)
                     .kind(ApplicationCommandType::ChatInput)
@@ -234,6 +402,11 @@ fn create_command(command: &ValidCommand, cmds: &mut CreateApplicationCommands)
                                     c.kind(ApplicationCommandOptionType::SubCommand)
                                         .name(&subcmd.name)
                                         .description(&subcmd.description);
+                                    insert_localizations(
+                                        &mut c.0,
+                                        &subcmd.name_localizations,
+                                        &subcmd.description_localizations,
+                                    );
                                     for arg in &subcmd.arguments.arguments {
                                         c.add_sub_option(arg.as_serenity_option());
                                     }
@@ -245,11 +418,21 @@ fn create_command(command: &ValidCommand, cmds: &mut CreateApplicationCommands)
                                         .name(&subcmdgroup.name)
                                         .description(&subcmdgroup.description)
                                         .clone();
+                                    insert_localizations(
+                                        &mut c.0,
+                                        &subcmdgroup.name_localizations,
+                                        &subcmdgroup.description_localizations,
+                                    );
                                     for subcmd in subcmdgroup.subcommands.values() {
                                         c.create_sub_option(|c| {
                                             c.kind(ApplicationCommandOptionType::SubCommand)
                                                 .name(&subcmd.name)
                                                 .description(&subcmd.description);
+                                            insert_localizations(
+                                                &mut c.0,
+                                                &subcmd.name_localizations,
+                                                &subcmd.description_localizations,
+                                            );
                                             for arg in &subcmd.arguments.arguments {
                                                 c.add_sub_option(arg.as_serenity_option());
                                             }
@@ -266,6 +449,44 @@ fn create_command(command: &ValidCommand, cmds: &mut CreateApplicationCommands)
     }
 }
 
+/// Converts the options of an invoked command into arguments
+async fn command_arguments(
+    ctx: &Context,
+    options: &[ApplicationCommandInteractionDataOption],
+) -> Result<Vec<CommandArgument>, ArgumentError> {
+    let mut args = Vec::new();
+    for opt in options {
+        let value = match &opt.resolved {
+            Some(arg) => Some(CommandArgumentValue::from_resolved(arg)?),
+            None => CommandArgumentValue::from_unresolved(ctx, opt).await,
+        };
+        args.push(CommandArgument {
+            name: opt.name.clone(),
+            value,
+        });
+    }
+    Ok(args)
+}
+
+/// Runs a command with the given options, reporting argument errors back to the invoker
+async fn run_command(
+    command: &Command,
+    context: Context,
+    options: &[ApplicationCommandInteractionDataOption],
+) {
+    let result = match command_arguments(&context, options).await {
+        Ok(args) => {
+            let args = ArgumentList::new(args, command.arguments.arguments.clone());
+            command.action.0(context.clone(), &args).await
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        let message = context.error_message(&e);
+        context.reply_ephemeral(message).await;
+    }
+}
+
 #[async_trait]
 impl EventHandler for Framework {
     async fn ready(&self, ctx: SerenityContext, _ready: Ready) {
@@ -294,7 +515,7 @@ impl EventHandler for Framework {
 
                 ApplicationCommand::set_global_application_commands(&ctx.http(), |c| {
                     for cmd in global_commands {
-                        create_command(cmd, c);
+                        create_command(cmd, &self.localizations, c);
                     }
                     c
                 })
@@ -307,7 +528,7 @@ impl EventHandler for Framework {
                     guild
                         .set_application_commands(&ctx.http(), |c| {
                             for cmd in commands {
-                                create_command(&cmd, c);
+                                create_command(&cmd, &self.localizations, c);
                             }
                             c
                         })
@@ -348,31 +569,9 @@ impl EventHandler for Framework {
 
                     match command.expect("Error finding command.") {
                         ValidCommand::Command(command) => {
-                            let context = Context::new(&ctx, &interaction_command);
-
-                            let options = interaction_command.data.options;
-
-                            let args: Result<Vec<CommandArgument>, _> = options
-                                .iter()
-                                .map(|opt| {
-                                    let value = match &opt.resolved {
-                                        Some(arg) => match CommandArgumentValue::from_resolved(arg)
-                                        {
-                                            Ok(v) => Some(v),
-                                            Err(e) => return Err(e),
-                                        },
-                                        None => None,
-                                    };
-                                    Ok(CommandArgument {
-                                        name: opt.name.clone(),
-                                        value,
-                                    })
-                                })
-                                .collect();
-                            if args.is_err() {
-                                return;
-                            }
-                            command.action.0(context, &ArgumentList::new(args.unwrap())).await;
+                            let context = self.context(&ctx, &interaction_command);
+
+                            run_command(command, context, &interaction_command.data.options).await;
                         }
                         ValidCommand::SubCommands(subcmds) => {
                             let options = &interaction_command.data.options;
@@ -382,36 +581,10 @@ impl EventHandler for Framework {
                                 {
                                     match called_sub_cmd {
                                         SubCommand::SubCommand(subcmd) => {
-                                            let context = Context::new(&ctx, &interaction_command);
-                                            let new_options = sub_cmd_opt.options.clone();
-
-                                            let args: Result<Vec<CommandArgument>, _> = new_options
-                                                .iter()
-                                                .map(|opt| {
-                                                    let value = match &opt.resolved {
-                                                        Some(arg) => match CommandArgumentValue::from_resolved(arg)
-                                                        {
-                                                            Ok(v) => Some(v),
-                                                            Err(e) => return Err(e),
-                                                        },
-                                                        None => None,
-                                                    };
-                                                    Ok(CommandArgument {
-                                                        name: opt.name.clone(),
-                                                        value,
-                                                    })
-                                                })
-                                                .collect();
-
-                                            if args.is_err() {
-                                                return;
-                                            }
+                                            let context = self.context(&ctx, &interaction_command);
 
-                                            subcmd.action.0(
-                                                context,
-                                                &ArgumentList::new(args.unwrap()),
-                                            )
-                                            .await;
+                                            run_command(subcmd, context, &sub_cmd_opt.options)
+                                                .await;
                                         }
                                         SubCommand::SubCommandGroup(subcmdgroup) => {
                                             if let Some(sub_cmd_opt) = sub_cmd_opt.options.get(0) {
@@ -419,34 +592,12 @@ impl EventHandler for Framework {
                                                     subcmdgroup.subcommands.get(&*sub_cmd_opt.name)
                                                 {
                                                     let context =
-                                                        Context::new(&ctx, &interaction_command);
-                                                    let new_options = sub_cmd_opt.options.clone();
-
-                                                    let args: Result<Vec<CommandArgument>, _> = new_options
-                                                        .iter()
-                                                        .map(|opt| {
-                                                            let value = match &opt.resolved {
-                                                                Some(arg) => match CommandArgumentValue::from_resolved(arg)
-                                                                {
-                                                                    Ok(v) => Some(v),
-                                                                    Err(e) => return Err(e),
-                                                                },
-                                                                None => None,
-                                                            };
-                                                            Ok(CommandArgument {
-                                                                name: opt.name.clone(),
-                                                                value,
-                                                            })
-                                                        })
-                                                        .collect();
-
-                                                    if args.is_err() {
-                                                        return;
-                                                    }
-
-                                                    subcmd.action.0(
+                                                        self.context(&ctx, &interaction_command);
+
+                                                    run_command(
+                                                        subcmd,
                                                         context,
-                                                        &ArgumentList::new(args.unwrap()),
+                                                        &sub_cmd_opt.options,
                                                     )
                                                     .await;
                                                 }
@@ -460,6 +611,37 @@ impl EventHandler for Framework {
                 }
                 return;
             }
+            InteractionType::ModalSubmit => {
+                let interaction = interaction.modal_submit().unwrap();
+                if let Collected::Unclaimed(interaction) = self.collectors.submit_modal(interaction)
+                {
+                    modal::expired(&interaction, &ctx.http).await;
+                }
+            }
+            InteractionType::MessageComponent => {
+                let interaction = interaction.message_component().unwrap();
+                let interaction = match self.collectors.submit_component(interaction) {
+                    Collected::Unclaimed(interaction) => interaction,
+                    Collected::Taken => return,
+                };
+                let source = interaction.message.interaction.as_ref().map(|i| i.id);
+                let context = ComponentContext::from_framework(
+                    &ctx,
+                    &interaction,
+                    self.data.clone(),
+                    self.custom_ids.clone(),
+                );
+                let handled = component::dispatch(&self.components, context.clone()).await;
+                // Components of a command that is waiting for its invoker
+                if !handled && matches!(source, Some(s) if self.collectors.awaits_reply(s)) {
+                    context
+                        .reply_ephemeral(
+                            "Only the user who used the command can use these controls",
+                        )
+                        .await
+                        .ok();
+                }
+            }
             _ => return,
         }
     }
@@ -0,0 +1,327 @@
+//! Modal forms, derived from structs with [`Modal`](crate::Modal) and shown with
+//! [`Context::show_modal`](crate::Context::show_modal)
+
+use crate::framework::collector::Collectors;
+use crate::framework::command::argument::ArgumentError;
+use crate::framework::context;
+use crate::framework::DataMap;
+use serenity::builder::CreateInputText;
+use serenity::http::Http;
+use serenity::model::interactions::application_command::ApplicationCommandInteraction;
+use serenity::model::interactions::message_component::{
+    ActionRowComponent, InputTextStyle, MessageComponentInteraction,
+};
+use serenity::model::interactions::modal::ModalSubmitInteraction;
+use serenity::model::interactions::InteractionResponseType;
+use serenity::model::prelude::InteractionApplicationCommandCallbackDataFlags;
+use serenity::prelude::Context as SerenityContext;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Style of a text input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputStyle {
+    /// Single line input
+    Short,
+    /// Multi line input
+    Paragraph,
+}
+
+/// A text input of a modal
+#[derive(Debug, Clone)]
+pub struct ModalField {
+    pub custom_id: String,
+    pub label: String,
+    pub style: InputStyle,
+    pub min_len: Option<u64>,
+    pub max_len: Option<u64>,
+    pub required: bool,
+    pub placeholder: Option<String>,
+    pub value: Option<String>,
+}
+
+impl ModalField {
+    fn build<'a>(&self, input: &'a mut CreateInputText) -> &'a mut CreateInputText {
+        input
+            .custom_id(&self.custom_id)
+            .label(&self.label)
+            .style(match self.style {
+                InputStyle::Short => InputTextStyle::Short,
+                InputStyle::Paragraph => InputTextStyle::Paragraph,
+            })
+            .required(self.required);
+        if let Some(min) = self.min_len {
+            input.min_length(min);
+        }
+        if let Some(max) = self.max_len {
+            input.max_length(max);
+        }
+        if let Some(placeholder) = &self.placeholder {
+            input.placeholder(placeholder);
+        }
+        if let Some(value) = &self.value {
+            input.value(value);
+        }
+        input
+    }
+}
+
+/// A form shown with [`Context::show_modal`](crate::Context::show_modal),
+/// usually implemented with `#[derive(Modal)]`
+pub trait Modal: Sized {
+    fn title() -> String;
+
+    /// Text inputs of the modal, at most 5
+    fn fields() -> Vec<ModalField>;
+
+    /// Builds the form from the submitted values
+    fn from_values(values: &ModalValues) -> Result<Self, ArgumentError>;
+}
+
+/// Values submitted in a modal, keyed by the custom id of each input
+#[derive(Debug, Clone, Default)]
+pub struct ModalValues(HashMap<String, String>);
+
+impl ModalValues {
+    pub fn from_interaction(interaction: &ModalSubmitInteraction) -> Self {
+        Self(
+            interaction
+                .data
+                .components
+                .iter()
+                .flat_map(|row| row.components.iter())
+                .filter_map(|component| match component {
+                    ActionRowComponent::InputText(input) => {
+                        Some((input.custom_id.clone(), input.value.clone()))
+                    }
+                    _ => None,
+                })
+                .collect(),
+        )
+    }
+
+    /// Gets the value of an input, `None` if it is missing or was left empty
+    pub fn get(&self, custom_id: &str) -> Option<&str> {
+        self.0
+            .get(custom_id)
+            .map(|value| value.as_str())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Parses the value of a required input
+    pub fn parse<T: FromStr>(&self, custom_id: &str, label: &str) -> Result<T, ArgumentError> {
+        self.parse_optional(custom_id, label)?.ok_or_else(|| {
+            ArgumentError::IncomingArgumentNotProvided(format!("`{}` is required", label))
+        })
+    }
+
+    /// Parses the value of an optional input, `None` if it was left empty
+    pub fn parse_optional<T: FromStr>(
+        &self,
+        custom_id: &str,
+        label: &str,
+    ) -> Result<Option<T>, ArgumentError> {
+        self.get(custom_id)
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    ArgumentError::InvalidValue(format!(
+                        "`{}` is not a valid value for {}",
+                        value, label
+                    ))
+                })
+            })
+            .transpose()
+    }
+}
+
+/// Error showing a modal
+#[derive(Debug)]
+pub enum ModalError {
+    /// The user did not submit the modal in time
+    TimedOut,
+    /// The modal could not be shown
+    Http(serenity::Error),
+    /// The submitted values could not be parsed, the error was already reported to the user
+    Invalid(ArgumentError),
+}
+
+impl Display for ModalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModalError::TimedOut => write!(f, "The form was not submitted in time"),
+            ModalError::Http(e) => write!(f, "Could not show the form: {}", e),
+            ModalError::Invalid(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ModalError {}
+
+/// A submitted modal, which still has to be responded to
+pub struct ModalSubmit<T> {
+    pub data: T,
+    interaction: ModalSubmitInteraction,
+    http: Arc<Http>,
+}
+
+impl<T> ModalSubmit<T> {
+    /// The raw interaction of the submission
+    pub fn interaction(&self) -> &ModalSubmitInteraction {
+        &self.interaction
+    }
+
+    pub async fn reply<S: Into<String>>(&self, msg: S) -> serenity::Result<()> {
+        self.interaction
+            .create_interaction_response(&self.http, |res| {
+                res.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|data| data.content(msg.into()))
+            })
+            .await
+    }
+
+    pub async fn reply_ephemeral<S: Into<String>>(&self, msg: S) -> serenity::Result<()> {
+        reply_ephemeral(&self.interaction, &self.http, msg.into()).await
+    }
+
+    /// Acknowledges the submission with a loading state, to follow up later
+    pub async fn defer(&self) -> serenity::Result<()> {
+        self.interaction
+            .create_interaction_response(&self.http, |res| {
+                res.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            })
+            .await
+    }
+}
+
+async fn reply_ephemeral(
+    interaction: &ModalSubmitInteraction,
+    http: &Http,
+    msg: String,
+) -> serenity::Result<()> {
+    interaction
+        .create_interaction_response(http, |res| {
+            res.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|data| {
+                    data.content(msg)
+                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                })
+        })
+        .await
+}
+
+/// Interaction a modal is shown in response to
+pub(crate) enum ModalSource<'a> {
+    Command(&'a ApplicationCommandInteraction),
+    Component(&'a MessageComponentInteraction),
+}
+
+/// Responds with a modal, returning its custom id and a receiver for its submission.
+/// The modal stays registered until it is submitted or cancelled with [`Collectors::cancel_modal`]
+pub(crate) async fn open<T: Modal>(
+    source: ModalSource<'_>,
+    http: &Http,
+    collectors: &Collectors,
+) -> Result<(String, oneshot::Receiver<ModalSubmitInteraction>), ModalError> {
+    let (id, user) = match &source {
+        ModalSource::Command(i) => (i.id, i.user.id),
+        ModalSource::Component(i) => (i.id, i.user.id),
+    };
+    let custom_id = format!("ataraxy:modal:{}", id);
+    let receiver = collectors.modal(custom_id.clone(), user);
+
+    let fields = T::fields();
+    // Both interactions take the same response builder, which is simplest to write out for each
+    macro_rules! respond {
+        ($interaction:expr) => {
+            $interaction
+                .create_interaction_response(http, |res| {
+                    res.kind(InteractionResponseType::Modal)
+                        .interaction_response_data(|data| {
+                            data.custom_id(&custom_id)
+                                .title(T::title())
+                                .components(|components| {
+                                    for field in &fields {
+                                        components.create_action_row(|row| {
+                                            row.create_input_text(|input| field.build(input))
+                                        });
+                                    }
+                                    components
+                                })
+                        })
+                })
+                .await
+        };
+    }
+    let shown = match source {
+        ModalSource::Command(i) => respond!(i),
+        ModalSource::Component(i) => respond!(i),
+    };
+    match shown {
+        Ok(()) => Ok((custom_id, receiver)),
+        Err(e) => {
+            collectors.cancel_modal(&custom_id);
+            Err(ModalError::Http(e))
+        }
+    }
+}
+
+/// Builds the form from a submission, reporting invalid values to the user
+pub(crate) async fn submitted<T: Modal>(
+    submitted: ModalSubmitInteraction,
+    http: Arc<Http>,
+    data: &DataMap,
+) -> Result<ModalSubmit<T>, ModalError> {
+    match T::from_values(&ModalValues::from_interaction(&submitted)) {
+        Ok(data) => Ok(ModalSubmit {
+            data,
+            interaction: submitted,
+            http,
+        }),
+        Err(e) => {
+            let mut locales = vec![submitted.locale.as_str()];
+            locales.extend(submitted.guild_locale.as_deref());
+            let message = context::error_message(data, &locales, &e);
+            reply_ephemeral(&submitted, &http, message).await.ok();
+            Err(ModalError::Invalid(e))
+        }
+    }
+}
+
+/// Responds to a command with a modal and waits for the user to submit it
+pub(crate) async fn show<T: Modal>(
+    serenity_context: &SerenityContext,
+    interaction: &ApplicationCommandInteraction,
+    collectors: &Collectors,
+    data: &DataMap,
+    timeout: Duration,
+) -> Result<ModalSubmit<T>, ModalError> {
+    let (custom_id, receiver) = open::<T>(
+        ModalSource::Command(interaction),
+        &serenity_context.http,
+        collectors,
+    )
+    .await?;
+
+    match tokio::time::timeout(timeout, receiver).await {
+        Ok(Ok(interaction)) => submitted(interaction, serenity_context.http.clone(), data).await,
+        _ => {
+            collectors.cancel_modal(&custom_id);
+            Err(ModalError::TimedOut)
+        }
+    }
+}
+
+/// Tells the user a modal can no longer be submitted, ie after its timeout
+pub(crate) async fn expired(interaction: &ModalSubmitInteraction, http: &Http) {
+    reply_ephemeral(
+        interaction,
+        http,
+        "This form has expired, please use the command again".to_string(),
+    )
+    .await
+    .ok();
+}
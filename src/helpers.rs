@@ -0,0 +1,320 @@
+//! Common interactive replies built on [`Context::collect_components`]
+//!
+//! Controls only respond to the user who invoked the command, and are disabled once the helper
+//! stops waiting for them.
+
+use crate::framework::command::argument::ArgumentError;
+use crate::framework::modal::{self, InputStyle, ModalField, ModalSource, ModalValues};
+use crate::{Context, Modal};
+use serenity::builder::{CreateComponents, CreateEmbed};
+use serenity::model::interactions::message_component::ButtonStyle;
+use serenity::model::interactions::modal::ModalSubmitInteraction;
+use serenity::model::interactions::InteractionResponseType;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// How long [`paginate`] waits for the next button click before disabling the controls
+pub const PAGINATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How long [`confirm`] waits for an answer, after which the prompt counts as cancelled
+pub const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+const FIRST: &str = "ataraxy:page:first";
+const PREVIOUS: &str = "ataraxy:page:previous";
+const JUMP: &str = "ataraxy:page:jump";
+const NEXT: &str = "ataraxy:page:next";
+const LAST: &str = "ataraxy:page:last";
+
+const CONFIRM: &str = "ataraxy:confirm:yes";
+const CANCEL: &str = "ataraxy:confirm:no";
+
+/// Buttons to go to the first page, go back, jump to a page, go forward and go to the last page.
+/// The jump button shows the current page
+fn page_controls(
+    components: &mut CreateComponents,
+    page: usize,
+    total: usize,
+    disabled: bool,
+) -> &mut CreateComponents {
+    components.create_action_row(|row| {
+        row.create_button(|b| {
+            b.custom_id(FIRST)
+                .label("«")
+                .style(ButtonStyle::Secondary)
+                .disabled(disabled || page == 0)
+        })
+        .create_button(|b| {
+            b.custom_id(PREVIOUS)
+                .label("‹")
+                .style(ButtonStyle::Primary)
+                .disabled(disabled || page == 0)
+        })
+        .create_button(|b| {
+            b.custom_id(JUMP)
+                .label(format!("{}/{}", page + 1, total))
+                .style(ButtonStyle::Secondary)
+                .disabled(disabled)
+        })
+        .create_button(|b| {
+            b.custom_id(NEXT)
+                .label("›")
+                .style(ButtonStyle::Primary)
+                .disabled(disabled || page + 1 == total)
+        })
+        .create_button(|b| {
+            b.custom_id(LAST)
+                .label("»")
+                .style(ButtonStyle::Secondary)
+                .disabled(disabled || page + 1 == total)
+        })
+    })
+}
+
+/// Modal shown by the jump button of [`paginate`]
+struct JumpToPage {
+    page: usize,
+}
+
+impl Modal for JumpToPage {
+    fn title() -> String {
+        "Jump to page".to_string()
+    }
+
+    fn fields() -> Vec<ModalField> {
+        vec![ModalField {
+            custom_id: "page".to_string(),
+            label: "Page".to_string(),
+            style: InputStyle::Short,
+            min_len: Some(1),
+            max_len: Some(6),
+            required: true,
+            placeholder: None,
+            value: None,
+        }]
+    }
+
+    fn from_values(values: &ModalValues) -> Result<Self, ArgumentError> {
+        Ok(Self {
+            page: values.parse("page", "Page")?,
+        })
+    }
+}
+
+/// Waits for the jump modal to be submitted, or forever if it is not shown
+async fn jump_submission(
+    jump: &mut Option<(String, oneshot::Receiver<ModalSubmitInteraction>)>,
+) -> Option<ModalSubmitInteraction> {
+    match jump {
+        Some((_, receiver)) => receiver.await.ok(),
+        None => std::future::pending().await,
+    }
+}
+
+/// Replies with the first page and buttons to browse the others, until no button was
+/// clicked for [`PAGINATION_TIMEOUT`]. The button showing the page number asks for a page
+/// to jump to. A single page is sent without buttons
+/// ```rust, no_run
+/// # use ataraxy::{helpers, Context};
+/// # use serenity::builder::CreateEmbed;
+/// # async fn leaderboard(ctx: Context, entries: Vec<String>) {
+/// let pages = entries
+///     .chunks(10)
+///     .map(|chunk| {
+///         let mut embed = CreateEmbed::default();
+///         embed.title("Leaderboard").description(chunk.join("\n"));
+///         embed
+///     })
+///     .collect();
+/// helpers::paginate(&ctx, pages).await.ok();
+/// # }
+/// ```
+pub async fn paginate(ctx: &Context, pages: Vec<CreateEmbed>) -> serenity::Result<()> {
+    let total = pages.len();
+    let first = match pages.first() {
+        Some(first) => first.clone(),
+        None => return Ok(()),
+    };
+
+    // Collecting starts before replying, so no click goes unanswered
+    let mut clicks = ctx.collect_components(|i| {
+        [FIRST, PREVIOUS, JUMP, NEXT, LAST].contains(&i.data.custom_id.as_str())
+    });
+    ctx.interaction()
+        .create_interaction_response(ctx.http(), |res| {
+            res.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|data| {
+                    data.set_embed(first);
+                    if total > 1 {
+                        data.components(|c| page_controls(c, 0, total, false));
+                    }
+                    data
+                })
+        })
+        .await?;
+    if total == 1 {
+        return Ok(());
+    }
+
+    let mut page: usize = 0;
+    let mut jump = None;
+    let result = loop {
+        tokio::select! {
+            click = clicks.next(PAGINATION_TIMEOUT) => {
+                let click = match click {
+                    Some(click) => click,
+                    None => break Ok(()),
+                };
+                if click.custom_id() == JUMP {
+                    if let Some((custom_id, _)) = jump.take() {
+                        ctx.collectors().cancel_modal(&custom_id);
+                    }
+                    jump = modal::open::<JumpToPage>(
+                        ModalSource::Component(click.interaction()),
+                        ctx.http(),
+                        ctx.collectors(),
+                    )
+                    .await
+                    .ok();
+                    continue;
+                }
+
+                page = match click.custom_id() {
+                    FIRST => 0,
+                    PREVIOUS => page.saturating_sub(1),
+                    NEXT => (page + 1).min(total - 1),
+                    _ => total - 1,
+                };
+                let embed = pages[page].clone();
+                let updated = click
+                    .interaction()
+                    .create_interaction_response(ctx.http(), |res| {
+                        res.kind(InteractionResponseType::UpdateMessage)
+                            .interaction_response_data(|data| {
+                                data.set_embed(embed)
+                                    .components(|c| page_controls(c, page, total, false))
+                            })
+                    })
+                    .await;
+                if let Err(e) = updated {
+                    break Err(e);
+                }
+            }
+            submitted = jump_submission(&mut jump) => {
+                jump = None;
+                let submitted = match submitted {
+                    Some(submitted) => submitted,
+                    None => continue,
+                };
+                let http = ctx.serenity_context().http.clone();
+                // Invalid pages were reported to the user by the modal
+                let submit = match modal::submitted::<JumpToPage>(submitted, http, ctx.data_map()).await {
+                    Ok(submit) => submit,
+                    Err(_) => continue,
+                };
+                page = submit.data.page.clamp(1, total) - 1;
+                let embed = pages[page].clone();
+                let updated = submit
+                    .interaction()
+                    .create_interaction_response(ctx.http(), |res| {
+                        res.kind(InteractionResponseType::UpdateMessage)
+                            .interaction_response_data(|data| {
+                                data.set_embed(embed)
+                                    .components(|c| page_controls(c, page, total, false))
+                            })
+                    })
+                    .await;
+                if let Err(e) = updated {
+                    break Err(e);
+                }
+            }
+        }
+    };
+    if let Some((custom_id, _)) = jump {
+        ctx.collectors().cancel_modal(&custom_id);
+    }
+    drop(clicks);
+    result?;
+
+    ctx.interaction()
+        .edit_original_interaction_response(ctx.http(), |res| {
+            res.components(|c| page_controls(c, page, total, true))
+        })
+        .await?;
+    Ok(())
+}
+
+/// Confirm and cancel buttons
+fn confirm_controls(components: &mut CreateComponents, disabled: bool) -> &mut CreateComponents {
+    components.create_action_row(|row| {
+        row.create_button(|b| {
+            b.custom_id(CONFIRM)
+                .label("Confirm")
+                .style(ButtonStyle::Success)
+                .disabled(disabled)
+        })
+        .create_button(|b| {
+            b.custom_id(CANCEL)
+                .label("Cancel")
+                .style(ButtonStyle::Danger)
+                .disabled(disabled)
+        })
+    })
+}
+
+/// Replies with a prompt and confirm and cancel buttons, returning whether the user confirmed.
+/// Returns `false` if the user cancelled, did not answer within [`CONFIRMATION_TIMEOUT`], or the
+/// prompt could not be sent. The buttons are disabled once answered, and the command can follow up
+/// through [`Context::interaction`]
+/// ```rust, no_run
+/// # use ataraxy::{helpers, Context};
+/// # async fn clear(ctx: Context) {
+/// if helpers::confirm(&ctx, "Delete all warnings?").await {
+///     // ...
+/// }
+/// # }
+/// ```
+pub async fn confirm<S: Into<String>>(ctx: &Context, prompt: S) -> bool {
+    // Collecting starts before replying, so an early answer is not missed
+    let mut answers =
+        ctx.collect_components(|i| [CONFIRM, CANCEL].contains(&i.data.custom_id.as_str()));
+    let sent = ctx
+        .interaction()
+        .create_interaction_response(ctx.http(), |res| {
+            res.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|data| {
+                    data.content(prompt.into())
+                        .components(|c| confirm_controls(c, false))
+                })
+        })
+        .await;
+    if sent.is_err() {
+        return false;
+    }
+
+    let answer = answers.next(CONFIRMATION_TIMEOUT).await;
+    drop(answers);
+    match answer {
+        Some(answer) => {
+            answer
+                .interaction()
+                .create_interaction_response(ctx.http(), |res| {
+                    res.kind(InteractionResponseType::UpdateMessage)
+                        .interaction_response_data(|data| {
+                            data.components(|c| confirm_controls(c, true))
+                        })
+                })
+                .await
+                .ok();
+            answer.custom_id() == CONFIRM
+        }
+        None => {
+            ctx.interaction()
+                .edit_original_interaction_response(ctx.http(), |res| {
+                    res.components(|c| confirm_controls(c, true))
+                })
+                .await
+                .ok();
+            false
+        }
+    }
+}
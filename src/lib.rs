@@ -5,11 +5,35 @@
 //! ```
 
 pub mod framework;
+pub mod helpers;
 mod numbers;
 
-pub use ataraxy_macros::command;
+/// Re-exported for date and duration arguments, see [`framework::command::time`]
+pub use chrono;
+#[cfg(feature = "chrono-tz")]
+pub use chrono_tz;
+/// Re-exported for [`Context::t`] arguments
+#[cfg(feature = "i18n")]
+pub use fluent_bundle;
+/// Re-exported for regular expressions used in `#[option(regex = "...")]`
+pub use regex;
+/// Re-exported for link arguments
+pub use url;
+
+#[doc(hidden)]
+pub use async_trait::async_trait;
+pub use ataraxy_macros::{command, component, CommandArguments, Modal};
+pub use framework::command::argument::{CommandArguments, Mentionable};
+pub use framework::command::extract::{Author, Data, FromContext, Locale};
 pub use framework::Command;
 pub use framework::CommandGroup;
+pub use framework::Component;
+pub use framework::ComponentContext;
 pub use framework::Context;
 pub use framework::Framework;
+pub use framework::Localizations;
+pub use framework::Modal;
+pub use framework::Payload;
 pub use framework::SubCommands;
+#[cfg(feature = "i18n")]
+pub use framework::{TranslationError, Translations};
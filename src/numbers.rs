@@ -1,108 +1,123 @@
+use std::convert::TryFrom;
+use std::num::{
+    NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU16, NonZeroU32,
+    NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+
 pub enum NumberType {
     Integer,
     Float,
 }
 
-pub trait Number {
-    const MIN: f64;
-    const MAX: f64;
-    fn from_f64(value: f64) -> Self;
-    fn number_type() -> NumberType;
-}
+/// Smallest value Discord accepts in integer and number options (-2^53)
+pub const DISCORD_MIN: f64 = -9007199254740992.0;
+/// Largest value Discord accepts in integer and number options (2^53)
+pub const DISCORD_MAX: f64 = 9007199254740992.0;
 
-impl Number for u8 {
-    const MIN: f64 = u8::MIN as f64;
-    const MAX: f64 = u8::MAX as f64;
-    fn from_f64(value: f64) -> Self {
-        value as u8
-    }
-    fn number_type() -> NumberType {
-        NumberType::Integer
-    }
+/// Clamps a bound to the range Discord accepts
+pub fn clamp_to_discord(value: f64) -> f64 {
+    value.clamp(DISCORD_MIN, DISCORD_MAX)
 }
 
-impl Number for u16 {
-    const MIN: f64 = u16::MIN as f64;
-    const MAX: f64 = u16::MAX as f64;
-    fn from_f64(value: f64) -> Self {
-        value as u16
-    }
-    fn number_type() -> NumberType {
-        NumberType::Integer
+/// Formats a bound of `T` as registered with Discord: clamped, and without a fractional part
+/// for integer types so large bounds are not rounded
+pub fn display_bound<T: Number>(value: f64) -> String {
+    let value = clamp_to_discord(value);
+    match T::number_type() {
+        NumberType::Integer => (value as i64).to_string(),
+        NumberType::Float => value.to_string(),
     }
 }
 
-impl Number for u32 {
-    const MIN: f64 = u32::MIN as f64;
-    const MAX: f64 = u32::MAX as f64;
-    fn from_f64(value: f64) -> Self {
-        value as u32
-    }
-    fn number_type() -> NumberType {
-        NumberType::Integer
-    }
+pub trait Number: Sized {
+    const MIN: f64;
+    const MAX: f64;
+    /// Whether zero is rejected, ie for `NonZeroI32` whose range includes it
+    const NON_ZERO: bool = false;
+    /// Converts an integer, returning `None` if it is out of range
+    fn from_i64(value: i64) -> Option<Self>;
+    /// Converts a float, returning `None` if it is out of range, or fractional for integer types
+    fn from_f64(value: f64) -> Option<Self>;
+    fn number_type() -> NumberType;
 }
 
-impl Number for u64 {
-    const MIN: f64 = u64::MIN as f64;
-    const MAX: f64 = u64::MAX as f64;
-    fn from_f64(value: f64) -> Self {
-        value as u64
-    }
-    fn number_type() -> NumberType {
-        NumberType::Integer
+/// Converts a float without a fractional part that fits in an `i64`
+fn integral(value: f64) -> Option<i64> {
+    if value.is_finite()
+        && value.fract() == 0.0
+        && value >= i64::MIN as f64
+        && value < i64::MAX as f64
+    {
+        Some(value as i64)
+    } else {
+        None
     }
 }
 
-impl Number for i8 {
-    const MIN: f64 = i8::MIN as f64;
-    const MAX: f64 = i8::MAX as f64;
-    fn from_f64(value: f64) -> Self {
-        value as i8
-    }
-    fn number_type() -> NumberType {
-        NumberType::Integer
-    }
+macro_rules! impl_integer {
+    ($($t:ty),*) => {$(
+        impl Number for $t {
+            const MIN: f64 = <$t>::MIN as f64;
+            const MAX: f64 = <$t>::MAX as f64;
+            fn from_i64(value: i64) -> Option<Self> {
+                <$t>::try_from(value).ok()
+            }
+            fn from_f64(value: f64) -> Option<Self> {
+                integral(value).and_then(Self::from_i64)
+            }
+            fn number_type() -> NumberType {
+                NumberType::Integer
+            }
+        }
+    )*};
 }
 
-impl Number for i16 {
-    const MIN: f64 = i16::MIN as f64;
-    const MAX: f64 = i16::MAX as f64;
-    fn from_f64(value: f64) -> Self {
-        value as i16
-    }
-    fn number_type() -> NumberType {
-        NumberType::Integer
-    }
-}
+impl_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
 
-impl Number for i32 {
-    const MIN: f64 = i32::MIN as f64;
-    const MAX: f64 = i32::MAX as f64;
-    fn from_f64(value: f64) -> Self {
-        value as i32
-    }
-    fn number_type() -> NumberType {
-        NumberType::Integer
-    }
+macro_rules! impl_non_zero {
+    ($($t:ty => $inner:ty),*) => {$(
+        impl Number for $t {
+            const MIN: f64 = if <$inner>::MIN == 0 { 1.0 } else { <$inner>::MIN as f64 };
+            const MAX: f64 = <$inner>::MAX as f64;
+            const NON_ZERO: bool = true;
+            fn from_i64(value: i64) -> Option<Self> {
+                <$inner>::from_i64(value).and_then(<$t>::new)
+            }
+            fn from_f64(value: f64) -> Option<Self> {
+                <$inner>::from_f64(value).and_then(<$t>::new)
+            }
+            fn number_type() -> NumberType {
+                NumberType::Integer
+            }
+        }
+    )*};
 }
 
-impl Number for i64 {
-    const MIN: f64 = i64::MIN as f64;
-    const MAX: f64 = i64::MAX as f64;
-    fn from_f64(value: f64) -> Self {
-        value as i64
-    }
-    fn number_type() -> NumberType {
-        NumberType::Integer
-    }
-}
+impl_non_zero!(
+    NonZeroU8 => u8,
+    NonZeroU16 => u16,
+    NonZeroU32 => u32,
+    NonZeroU64 => u64,
+    NonZeroUsize => usize,
+    NonZeroI8 => i8,
+    NonZeroI16 => i16,
+    NonZeroI32 => i32,
+    NonZeroI64 => i64,
+    NonZeroIsize => isize
+);
 
 impl Number for f32 {
     const MIN: f64 = f32::MIN as f64;
     const MAX: f64 = f32::MAX as f64;
-    fn from_f64(value: f64) -> Self {
-        value as f32
+    fn from_i64(value: i64) -> Option<Self> {
+        Some(value as f32)
+    }
+    fn from_f64(value: f64) -> Option<Self> {
+        if value.is_finite() && value.abs() <= f32::MAX as f64 {
+            Some(value as f32)
+        } else {
+            None
+        }
     }
     fn number_type() -> NumberType {
         NumberType::Float
@@ -112,10 +127,108 @@ impl Number for f32 {
 impl Number for f64 {
     const MIN: f64 = f64::MIN;
     const MAX: f64 = f64::MAX;
-    fn from_f64(value: f64) -> Self {
-        value
+    fn from_i64(value: i64) -> Option<Self> {
+        Some(value as f64)
+    }
+    fn from_f64(value: f64) -> Option<Self> {
+        if value.is_finite() {
+            Some(value)
+        } else {
+            None
+        }
     }
     fn number_type() -> NumberType {
         NumberType::Float
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2^53, the largest integer Discord accepts
+    const MAX_SAFE: i64 = 9007199254740992;
+
+    #[test]
+    fn converts_integral_floats() {
+        assert_eq!(integral(0.0), Some(0));
+        assert_eq!(integral(-5.0), Some(-5));
+        assert_eq!(integral(DISCORD_MAX), Some(MAX_SAFE));
+        assert_eq!(integral(DISCORD_MIN), Some(-MAX_SAFE));
+        assert_eq!(integral(i64::MIN as f64), Some(i64::MIN));
+        // `i64::MAX as f64` rounds up to 2^63, which does not fit in an `i64`
+        assert_eq!(integral(i64::MAX as f64), None);
+        assert_eq!(integral(1.5), None);
+        assert_eq!(integral(f64::NAN), None);
+        assert_eq!(integral(f64::INFINITY), None);
+        assert_eq!(integral(f64::NEG_INFINITY), None);
+    }
+
+    #[test]
+    fn converts_integers() {
+        assert_eq!(u8::from_i64(255), Some(255));
+        assert_eq!(u8::from_i64(256), None);
+        assert_eq!(i8::from_i64(-128), Some(-128));
+        assert_eq!(i8::from_i64(-129), None);
+        assert_eq!(u64::from_i64(-1), None);
+        assert_eq!(u64::from_i64(MAX_SAFE), Some(MAX_SAFE as u64));
+        assert_eq!(i64::from_i64(i64::MIN), Some(i64::MIN));
+        assert_eq!(usize::from_i64(MAX_SAFE), Some(MAX_SAFE as usize));
+        assert_eq!(isize::from_i64(-MAX_SAFE), Some(-MAX_SAFE as isize));
+
+        assert_eq!(u64::from_f64(DISCORD_MAX), Some(MAX_SAFE as u64));
+        assert_eq!(i64::from_f64(DISCORD_MIN), Some(-MAX_SAFE));
+        assert_eq!(i64::from_f64(i64::MAX as f64), None);
+        assert_eq!(u64::from_f64(-1.0), None);
+        assert_eq!(i32::from_f64(2.5), None);
+        assert_eq!(i32::from_f64(f64::NAN), None);
+        assert_eq!(u32::from_f64(f64::INFINITY), None);
+    }
+
+    #[test]
+    fn converts_non_zero_integers() {
+        assert_eq!(NonZeroU8::from_i64(0), None);
+        assert_eq!(NonZeroU16::from_i64(0), None);
+        assert_eq!(NonZeroU32::from_i64(0), None);
+        assert_eq!(NonZeroU64::from_i64(0), None);
+        assert_eq!(NonZeroUsize::from_i64(0), None);
+        assert_eq!(NonZeroI8::from_i64(0), None);
+        assert_eq!(NonZeroI16::from_i64(0), None);
+        assert_eq!(NonZeroI32::from_i64(0), None);
+        assert_eq!(NonZeroI64::from_i64(0), None);
+        assert_eq!(NonZeroIsize::from_i64(0), None);
+        assert_eq!(NonZeroU8::from_f64(0.0), None);
+        assert_eq!(NonZeroI64::from_f64(-0.0), None);
+
+        assert_eq!(NonZeroU8::from_i64(1), NonZeroU8::new(1));
+        assert_eq!(NonZeroU8::from_i64(256), None);
+        assert_eq!(NonZeroI8::from_i64(-128), NonZeroI8::new(-128));
+        assert_eq!(
+            NonZeroI64::from_f64(DISCORD_MIN),
+            NonZeroI64::new(-MAX_SAFE)
+        );
+        assert_eq!(<NonZeroU32 as Number>::MIN, 1.0);
+        assert_eq!(<NonZeroI32 as Number>::MIN, i32::MIN as f64);
+    }
+
+    #[test]
+    fn converts_floats() {
+        assert_eq!(f32::from_f64(1.5), Some(1.5));
+        assert_eq!(f32::from_f64(f32::MAX as f64), Some(f32::MAX));
+        assert_eq!(f32::from_f64(1e39), None);
+        assert_eq!(f32::from_f64(-1e39), None);
+        assert_eq!(f32::from_f64(f64::NAN), None);
+        assert_eq!(f64::from_f64(f64::INFINITY), None);
+        assert_eq!(f64::from_f64(f64::MAX), Some(f64::MAX));
+        assert_eq!(f64::from_i64(-3), Some(-3.0));
+    }
+
+    #[test]
+    fn clamps_bounds_to_discord() {
+        assert_eq!(clamp_to_discord(u64::MAX as f64), DISCORD_MAX);
+        assert_eq!(clamp_to_discord(i64::MIN as f64), DISCORD_MIN);
+        assert_eq!(clamp_to_discord(f64::MAX), DISCORD_MAX);
+        assert_eq!(clamp_to_discord(-100.0), -100.0);
+        assert_eq!(clamp_to_discord(255.0), 255.0);
+    }
+}